@@ -1,4 +1,5 @@
-use super::{ILP, Vector, ILPError, IntData, Cost};
+use super::{ILP, Vector, ILPError, IntData, Cost, SolverOptions, ProgressEvent, TieBreak};
+use super::heuristics;
 use std::time::Instant;
 use std::cmp::max;
 use std::{f64, i32};
@@ -7,12 +8,15 @@ type Map<K,V> = hashbrown::HashMap<K,V>;
 type LookupTable = Map<Vector, (Vector, Cost)>;
 type IterationData = (Vector, usize); // (scaled b, max iterations)
 
+/// Number of combined pairs between two checks of the time limit (a power of 2).
+const TIME_CHECK_INTERVAL: usize = 1 << 16;
+
 /*
     based on https://arxiv.org/abs/1803.04744
 */
 
-pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
-    println!("Solving ILP with the Jansen & Rohwedder algorithm...");
+pub fn solve(ilp:&ILP, options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> Result<Vector, ILPError> {
+    log!("Solving ILP with the Jansen & Rohwedder algorithm...");
     let start = Instant::now();
 
     // constants
@@ -24,8 +28,18 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
     let b_bound = (4.0 * H).ceil() as i32;
     let zero_check = !ilp.A.non_negative();
 
-    println!(" -> H = {} >= herdisc(A)", H);
-    println!(" -> K = {}", K);
+    log!(" -> H = {} >= herdisc(A)", H);
+    log!(" -> K = {}", K);
+    if let Some(cap) = options.max_var_value {
+        log!(" -> Warning: \u{2016}x\u{2016}\u{2081} is capped at {}, solutions might be missed!", cap);
+    }
+
+    if options.explain {
+        log!("    The lookup table maps right-hand sides b' to the best known x with Ax=b'.");
+        log!("    It starts with 0 and the columns of A. The target is doubled step by step");
+        log!("    (b * 2^(i-K) for i=1..K) and in each step sums of two table entries are");
+        log!("    kept if they are within distance 4H = {} of the current target.", b_bound);
+    }
 
     let mut solutions = LookupTable::with_capacity(1024);
     let mut has_zero_solution = false;
@@ -33,50 +47,59 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
     // i=0 (trivial solutions)
     solutions.insert(Vector::zero(m), (Vector::zero(n), 0));
     for (i, (column, &cost)) in ilp.A.iter().zip(ilp.c.iter()).enumerate() {
-        solutions.insert(column.clone(), (Vector::unit(n, i), cost));
+        // equal columns (without preprocessing): keep the one with the higher cost
+        let insert = match solutions.get(column) {
+            Some(&(_, c)) => c < cost,
+            None => true
+        };
+        if insert {
+            solutions.insert(column.clone(), (Vector::unit(n, i), cost));
+        }
     }
 
     // pre-compute main iteration
-    let mut iterations = Vec::<IterationData>::new();
-    {
-        let mut last = (compute_sb(&Vector::zero(m), K, 1), 1); // i=1
-
-        // i={1,...,K}
-        for i in 1..K+1 {
-            let sb = compute_sb(&ilp.b, K, i); // b * 2^{i-K}
-
-            if sb != last.0 {
-                iterations.push(last);
-                last = (sb, 1);
-
-                if i == K {
-                    iterations.push(last.clone());
-                }
-            } else {
-                last.1 += 1;
-            }
-        }
-
-        assert_eq!(last.0, ilp.b);
-        println!(" -> Iterations: {}", iterations.len());
-    }
+    let iterations = compute_iterations(ilp, K);
+    log!(" -> Iterations: {}", iterations.len());
 
     let mut last_solutions = solutions.clone();
     let mut new_solutions  = LookupTable::with_capacity(512);
     let mut x_bound:f64 = 1.0;
+    let mut pairs:usize = 0; // combined pairs of table entries
+    let mut accepted:usize = 0; // pairs close to the target with a small enough x
+    let mut final_target = None;
     
-    println!(" -> Building lookup table...");
+    log!(" -> Building lookup table...");
     for (sb, it_max) in iterations {
-        println!("    > size: {}", solutions.len());
+        progress(ProgressEvent::TableSize { size: solutions.len() });
+        let size_before = solutions.len();
+
+        if options.explain {
+            log!("      target {:?} for {} step(s) (i.e. until the target changes)", sb, it_max);
+        }
 
         for j in 0..it_max {
+            if options.time_limit_reached(start) {
+                return timeout_result(ilp, &solutions);
+            }
+
             x_bound *= 1.2;
             let x_ibound = f64::min(i32::MAX as f64, x_bound.ceil()) as i32;
+            let x_ibound = match options.max_var_value {
+                Some(cap) => i32::min(x_ibound, cap),
+                None => x_ibound
+            };
 
             // generate new solutions
             let iterator = if j==0 { solutions.iter() } else { last_solutions.iter() };
             for (k, (b1, (x1,c1))) in iterator.enumerate() {
-                for (b2, (x2,c2))  in solutions.iter().skip(if j==0 {k+1} else {0}) {
+                // for j=0 every unordered pair (including an entry with itself) is combined
+                for (b2, (x2,c2))  in solutions.iter().skip(if j==0 {k} else {0}) {
+                    // a single iteration can take very long for large tables
+                    pairs += 1;
+                    if pairs & (TIME_CHECK_INTERVAL - 1) == 0 && options.time_limit_reached(start) {
+                        return timeout_result(ilp, &solutions);
+                    }
+
                     let b = b1.add(b2);
                     let x = x1.add(x2);
                     let c = c1+c2;
@@ -84,16 +107,27 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
                     if zero_check && !has_zero_solution {
                         if b.is_zero() && x.dot(&ilp.c) > 0 {
                             has_zero_solution = true;
-                            println!(" -> Found a solution for Ax=0! ILP might be unbounded.");
+                            log!(" -> Found a solution for Ax=0! ILP might be unbounded.");
                         }
                     }
 
                     if !sb.max_distance(&b, b_bound) || x.one_norm() > x_ibound {
                         continue;
                     }
+                    accepted += 1;
 
-                    let insert = match solutions.get(&b) {
-                        Some(&(_,cost)) => cost < c,
+                    // a candidate found earlier in this iteration is at least as
+                    // good as the table entry and must not be overwritten by a worse one
+                    let insert = match new_solutions.get(&b).or_else(|| solutions.get(&b)) {
+                        Some((x_old, cost)) if *cost == c => match &ilp.c2 {
+                            Some(c2) if x.dot(c2) != x_old.dot(c2) => x.dot(c2) > x_old.dot(c2),
+                            _ => match options.tiebreak {
+                                TieBreak::First => false,
+                                TieBreak::MinL1 => x.one_norm() < x_old.one_norm(),
+                                TieBreak::MinSupport => x.nnz() < x_old.nnz()
+                            }
+                        },
+                        Some((_, cost)) => *cost < c,
                         None => true
                     };
 
@@ -113,6 +147,10 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
                 solutions.insert(b.clone(), x.clone());
             }
 
+            if let Some((_, cost)) = new_solutions.get(&ilp.b) {
+                progress(ProgressEvent::Incumbent { cost: *cost });
+            }
+
             // swap buffers
             {
                 let tmp = last_solutions;
@@ -123,10 +161,21 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
         }
 
         last_solutions.clear();
+
+        if options.explain {
+            log!("      the table grew by {} entries, x_bound is now {}", solutions.len() - size_before, x_bound.ceil());
+        }
+
+        final_target = Some(sb);
     }
 
-    println!(" -> Done. Final size: {}.", solutions.len());
-    println!(" -> {:?} elapsed.", start.elapsed());
+    log!(" -> Done. Final size: {}.", solutions.len());
+    if pairs > 0 {
+        log!(" -> Combined pairs: {}, accepted: {} ({:.1}%)", pairs, accepted, 100.0 * accepted as f64 / pairs as f64);
+    } else {
+        log!(" -> Combined pairs: 0 (the table only contains the columns of A)");
+    }
+    log!(" -> {:?} elapsed.", start.elapsed());
 
     match solutions.get(&ilp.b) {
         Some((x,_)) => {
@@ -134,17 +183,96 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
             if has_zero_solution {
                 return Err(ILPError::Unbounded);
             } else {
-                println!(" -> The ILP has a (bounded) solution.");
-                println!(" -> Solution cost: {}", x.dot(&ilp.c));
+                log!(" -> The ILP has a (bounded) solution.");
+                log!(" -> Solution cost: {}", x.dot(&ilp.c));
                 Ok(x.clone())
             }
         },
-        None => Err(ILPError::NoSolution)
+        None => {
+            // the last scaled target has to be b, otherwise the table does not cover b
+            if final_target.as_ref() != Some(&ilp.b) {
+                log!(" -> Warning: the final target {:?} is not b, the ILP might have a solution!", final_target);
+            } else if options.max_var_value.is_some() {
+                log!(" -> Warning: \u{2016}x\u{2016}\u{2081} was capped, the ILP might have a solution!");
+            } else {
+                log!(" -> b was within search bounds, instance is infeasible.");
+            }
+            Err(ILPError::NoSolution)
+        }
     }
 }
 
+/// The result if the time limit is reached: the better one of the known
+/// solution for b and the greedy solution (if there is one), which is not
+/// proven to be optimal.
+fn timeout_result(ilp:&ILP, solutions:&LookupTable) -> Result<Vector, ILPError> {
+    let known = solutions.get(&ilp.b).map(|(x,_)| x.clone());
+
+    match (known, heuristics::greedy_solve(ilp)) {
+        (Some(x), Some(greedy)) if greedy.dot(&ilp.c) > x.dot(&ilp.c) => {
+            log!(" -> Time limit, returning heuristic solution (not proven optimal).");
+            Ok(greedy)
+        },
+        (Some(x), _) => {
+            log!(" -> Time limit, returning the best known solution (not proven optimal).");
+            Ok(x)
+        },
+        (None, Some(greedy)) => {
+            log!(" -> Time limit, returning heuristic solution (not proven optimal).");
+            Ok(greedy)
+        },
+        (None, None) => Err(ILPError::Timeout)
+    }
+}
+
+/// Rough estimate of the final (= peak, the table only grows) lookup table
+/// size: the number of lattice points within distance 4H (inf-norm) of the
+/// scaled right-hand sides. Saturates at `u64::MAX`.
+pub fn estimate_table_size(ilp:&ILP) -> u64 {
+    let m = ilp.A.size.0 as i32;
+    let b_bound = (4.0 * ilp.A.herdisc_upper_bound()).ceil() as f64;
+    let iterations = compute_iterations(ilp, compute_K(ilp)).len() as f64;
+
+    (iterations * (2.0 * b_bound + 1.0).powi(m)) as u64
+}
+
+/// The right-hand sides the solver computes solutions for, in order:
+/// b * 2^{i-K} (rounded) for i={1,...,K} without repetitions. The first
+/// one is (usually) 0 and the last one is b.
+pub fn scaled_targets(ilp:&ILP) -> Vec<Vector> {
+    compute_iterations(ilp, compute_K(ilp)).into_iter().map(|(sb, _)| sb).collect()
+}
+
+/// Scaled right-hand sides b * 2^{i-K} for i={1,...,K} together with the
+/// number of consecutive i that yield the same (rounded) vector.
+#[allow(non_snake_case)]
+fn compute_iterations(ilp:&ILP, K:usize) -> Vec<IterationData> {
+    let m = ilp.A.size.0;
+    let mut iterations = Vec::new();
+    let mut last = (compute_sb(&Vector::zero(m), K, 1), 1); // i=1
+
+    // i={1,...,K}
+    for i in 1..K+1 {
+        let sb = compute_sb(&ilp.b, K, i); // b * 2^{i-K}
+
+        if sb != last.0 {
+            iterations.push(last);
+            last = (sb, 1);
+
+            if i == K {
+                iterations.push(last.clone());
+            }
+        } else {
+            last.1 += 1;
+        }
+    }
+
+    assert_eq!(last.0, ilp.b);
+    iterations
+}
+
 #[allow(non_snake_case)]
-fn compute_K(ilp:&ILP) -> usize {
+pub fn compute_K(ilp:&ILP) -> usize {
     let n = ilp.A.size.0 as f64;
     let m = ilp.A.size.0 as i32;
 
@@ -158,13 +286,27 @@ fn compute_K(ilp:&ILP) -> usize {
 
 fn compute_sb(b:&Vector, k:usize, i:usize) -> Vector {
     debug_assert!(k >= i);
-    let s = 0.5f64.powi(k as i32 - i as i32);
+    let shift = k - i; // b * 2^{-shift}
     let mut v = Vector::new(b.len());
 
     for &bv in b.iter() {
-        let x = bv as f64 * s;
-        v.data.push(x.round() as IntData);
+        v.data.push(round_shift(bv, shift));
     }
 
     v
 }
+
+/// Computes round(x / 2^shift) exactly (rounding half away from zero like
+/// `f64::round`). Floats could drift for large values and many halvings.
+fn round_shift(x:IntData, shift:usize) -> IntData {
+    if shift > 32 {
+        // |x| < 2^31, thus |x| / 2^shift < 1/2
+        return 0;
+    }
+
+    let abs = (x as i64).abs();
+    let half = (1i64 << shift) >> 1;
+    let rounded = if shift == 0 { abs } else { (abs + half) >> shift };
+
+    (rounded * (x as i64).signum()) as IntData
+}
@@ -0,0 +1,55 @@
+use super::{ILP, Vector, Matrix, VarMapping};
+
+/*
+    Small well-known ILPs for benchmarks and tests (feature `fixtures`).
+    The expected results are documented at the functions.
+*/
+
+/// max 3x1 + 5x2 s.t. 2x1 + 3x2 + s = 7 (an integer knapsack).
+/// Optimal: x = [2, 1, 0] with cost 11.
+pub fn knapsack() -> ILP {
+    ILP::with_named_vars(
+        Matrix::from_slice(1, 3, &[2, 3, 1]),
+        Vector::from_slice(&[7]),
+        Vector::from_slice(&[3, 5, 0]),
+        names(2)
+    ).expect("the fixture is valid")
+}
+
+/// max x1 + x2 s.t. 2x1 + 4x2 = 7.
+/// No solution (the left-hand side is even).
+pub fn infeasible() -> ILP {
+    ILP::with_named_vars(
+        Matrix::from_slice(1, 2, &[2, 4]),
+        Vector::from_slice(&[7]),
+        Vector::from_slice(&[1, 1]),
+        names(2)
+    ).expect("the fixture is valid")
+}
+
+/// max x1 + x2 + x3 s.t. x1 - x2 + k*x3 = k for k = 1, 2, 3.
+/// Unbounded (x1 and x2 can be increased by the same amount).
+pub fn unbounded() -> ILP {
+    ILP::with_named_vars(
+        Matrix::from_slice(3, 3, &[1, 1, 1, -1, -1, -1, 1, 2, 3]),
+        Vector::from_slice(&[1, 2, 3]),
+        Vector::from_slice(&[1, 1, 1]),
+        names(3)
+    ).expect("the fixture is valid")
+}
+
+/// max x1 + 2x2 + 3x3 + x4 s.t. x1 + x2 = 3 and 2x3 + x4 = 4,
+/// two independent blocks. Optimal: x = [0, 3, 2, 0] with cost 12.
+pub fn blocks() -> ILP {
+    ILP::with_named_vars(
+        Matrix::from_slice(2, 4, &[1, 0, 1, 0, 0, 2, 0, 1]),
+        Vector::from_slice(&[3, 4]),
+        Vector::from_slice(&[1, 2, 3, 1]),
+        names(4)
+    ).expect("the fixture is valid")
+}
+
+/// x1, x2, ... for the first n columns.
+fn names(n:usize) -> Vec<VarMapping> {
+    (0..n).map(|j| (format!("x{}", j+1), j)).collect()
+}
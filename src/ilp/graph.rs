@@ -1,5 +1,6 @@
 use std::ops::Range;
-use super::{Vector, Cost};
+use std::mem::size_of;
+use super::{Vector, Cost, IntData};
 
 type Map<K,V> = hashbrown::HashMap<K,V>;
 pub type NodeIdx = usize;
@@ -7,15 +8,24 @@ pub type ColumnIdx = usize;
 
 /*  A node contains its outgoing edges, thus an edge only
     stores one end index and the column that was used.
+    Nodes are numbered in insertion order and edges are stored in the
+    order they were added, the map is only used for lookups. The solvers
+    never depend on the (randomized) iteration order of the map, thus
+    the results are reproducible.
  */
 pub type Edge = (NodeIdx, ColumnIdx);
 
+/// Cost of a node that has not been reached (yet) by the longest path search.
+pub const UNREACHED: Cost = Cost::MIN;
+
 #[derive(Clone)]
 pub struct Node {
     pub idx: NodeIdx,
     pub predecessor: NodeIdx,
     pub via: ColumnIdx,
     pub cost: Cost,
+    pub cost2: Cost, // secondary cost (tie-breaker) of the path to this node
+    pub length: usize, // number of edges of the path to this node
     pub edges: Vec<Edge>
 }
 
@@ -77,7 +87,9 @@ impl VectorDiGraph {
             edges: Vec::with_capacity(self.edges_per_node),
             predecessor: pre,
             via: via,
-            cost: cost
+            cost: cost,
+            cost2: 0,
+            length: if self.nodes.is_empty() { 0 } else { self.nodes[pre].length + 1 }
         };
         let node_idx = node.idx;
         self.nodes.push(node);
@@ -92,6 +104,18 @@ impl VectorDiGraph {
         self.edges += 1;
     }
 
+    /// Resets the longest path state (cost, predecessor, via) of every node,
+    /// leaving the edges intact. Only the origin (node 0) is reached afterwards.
+    pub fn reset_path_state(&mut self) {
+        for node in self.nodes.iter_mut() {
+            node.predecessor = 0;
+            node.via = 0;
+            node.length = 0;
+            node.cost2 = 0;
+            node.cost = if node.idx == 0 { 0 } else { UNREACHED };
+        }
+    }
+
     pub fn iter_nodes(&self) -> Range<usize> {
         1..self.nodes.len()
     }
@@ -99,4 +123,29 @@ impl VectorDiGraph {
     pub fn num_edges(&self) -> usize {
         self.edges
     }
+
+    /// Rough estimate of the memory used by the graph in bytes: every node
+    /// and its vector (with `rows` entries) in the map, plus the edges.
+    pub fn estimated_memory(&self, rows:usize) -> usize {
+        let vector = size_of::<Vector>() + rows * size_of::<IntData>();
+        let entry = vector + size_of::<NodeIdx>();
+
+        self.nodes.len() * (size_of::<Node>() + entry) + self.edges * size_of::<Edge>()
+    }
+
+    /// The vectors of the nodes in arbitrary order (differs between runs),
+    /// use `vectors_by_idx` if the order matters.
+    pub fn iter_vectors(&self) -> impl Iterator<Item=&Vector> {
+        self.map.keys()
+    }
+
+    /// The vector of every node, indexed by the node index.
+    pub fn vectors_by_idx(&self) -> Vec<&Vector> {
+        let mut vectors = vec![None; self.nodes.len()];
+        for (v, &idx) in self.map.iter() {
+            vectors[idx] = Some(v);
+        }
+
+        vectors.into_iter().map(|v| v.expect("node without vector")).collect()
+    }
 }
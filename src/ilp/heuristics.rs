@@ -0,0 +1,96 @@
+use super::{ILP, Vector, IntData, Cost};
+
+/*
+    Fast heuristics, they do not necessarily find an (optimal) solution.
+*/
+
+/// Number of the best columns a randomized greedy step chooses from.
+const CANDIDATES:usize = 3;
+
+/// Greedy construction: starting at x = 0 the column with the highest cost
+/// among the columns that bring Ax closer to b (in the 1-norm) is added
+/// until Ax = b. Returns None if no column makes progress before b is
+/// reached (the ILP might still have a solution).
+pub fn greedy_solve(ilp:&ILP) -> Option<Vector> {
+    greedy_solve_seeded(ilp, 0)
+}
+
+/// Like `greedy_solve` for seed 0. For other seeds every step picks a random
+/// column among the (up to) `CANDIDATES` best ones, which leads the
+/// construction in a different direction.
+pub fn greedy_solve_seeded(ilp:&ILP, seed:u64) -> Option<Vector> {
+    let n = ilp.A.size.1;
+    let mut x = Vector::zero(n);
+    let mut distance = ilp.b.one_norm(); // ‖b - Ax‖₁
+    let mut residual = ilp.b.clone(); // b - Ax
+    let mut rng = if seed == 0 { None } else { Some(XorShift::new(seed)) };
+
+    while distance > 0 {
+        // (column, cost, new distance) of the columns that make progress
+        let mut candidates:Vec<(usize, IntData, IntData)> = Vec::new();
+
+        for (j, (column, &c)) in ilp.A.iter().zip(ilp.c.iter()).enumerate() {
+            let d:IntData = residual.iter().zip(column.iter()).map(|(&r, &a)| (r - a).abs()).sum();
+            if d < distance {
+                candidates.push((j, c, d));
+            }
+        }
+
+        // highest cost first, ties: the column that gets closer to b (stable, the first column wins)
+        candidates.sort_by_key(|&(_, c, d)| (-c, d));
+        let pick = match rng.as_mut() {
+            Some(rng) if !candidates.is_empty() => rng.below(usize::min(CANDIDATES, candidates.len())),
+            _ => 0
+        };
+
+        let &(j, _, d) = candidates.get(pick)?;
+        x.data[j] += 1;
+        for (r, &a) in residual.data.iter_mut().zip(ilp.A.columns[j].iter()) {
+            *r -= a;
+        }
+        distance = d;
+    }
+
+    Some(x)
+}
+
+/// Runs `greedy_solve` and then `greedy_solve_seeded` with the seeds
+/// 1..=restarts, returns the solution with the highest cost. The runs of
+/// fewer restarts are a prefix, so more restarts never give a worse solution.
+pub fn greedy_restarts(ilp:&ILP, restarts:usize) -> Option<Vector> {
+    let mut best:Option<(Vector, Cost)> = None;
+
+    for seed in 0..=restarts as u64 {
+        if let Some(x) = greedy_solve_seeded(ilp, seed) {
+            let cost = x.dot(&ilp.c);
+            let better = match &best {
+                Some((_, best_cost)) => cost > *best_cost,
+                None => true
+            };
+
+            if better {
+                best = Some((x, cost));
+            }
+        }
+    }
+
+    best.map(|(x, _)| x)
+}
+
+/// xorshift64*, good enough to vary the column choices.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed:u64) -> Self {
+        // the state must not be 0
+        XorShift(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    /// A random number in 0..n (n > 0).
+    fn below(&mut self, n:usize) -> usize {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32) as usize % n
+    }
+}
@@ -0,0 +1,92 @@
+use super::{ILP, Vector, ILPError, Cost, IntData, SolverOptions, TieBreak};
+use std::time::Instant;
+
+/*
+    Integer knapsack problems (a single constraint a*x = b with a >= 0)
+    are solved with the classic dynamic program in O(n*b).
+*/
+
+/// Largest right-hand side the dynamic program is used for (memory).
+const MAX_CAPACITY: IntData = 1 << 24;
+
+/// Returns true if the ILP is an integer knapsack problem that `solve`
+/// can handle: a single constraint with non-negative coefficients
+/// (and no secondary objective).
+pub fn applies(ilp:&ILP) -> bool {
+    ilp.A.size.0 == 1 && ilp.A.non_negative() && ilp.b.data[0] <= MAX_CAPACITY && ilp.c2.is_none()
+}
+
+pub fn solve(ilp:&ILP, options:&SolverOptions) -> Result<Vector, ILPError> {
+    assert!(applies(ilp), "the ILP is not a knapsack problem");
+    log!("Solving knapsack problem with dynamic programming...");
+    let start = Instant::now();
+
+    let n = ilp.A.size.1;
+    if ilp.b.data[0] < 0 {
+        return Err(ILPError::NoSolution);
+    }
+    let capacity = ilp.b.data[0] as usize;
+    let weights:Vec<usize> = ilp.A.iter().map(|column| column.data[0] as usize).collect();
+
+    // best[w]: highest cost of an x with a*x = w (if there is one),
+    // item[w]: an item of such an x, items[w]: the number of items (‖x‖₁)
+    let mut best:Vec<Option<Cost>> = vec![None; capacity + 1];
+    let mut item = vec![0; capacity + 1];
+    let mut items = vec![0; capacity + 1];
+    best[0] = Some(0);
+
+    for w in 1..capacity+1 {
+        if w % 4096 == 0 && options.time_limit_reached(start) {
+            return Err(ILPError::Timeout);
+        }
+
+        for (j, (&a, &c)) in weights.iter().zip(ilp.c.iter()).enumerate() {
+            if a == 0 || a > w {
+                continue;
+            }
+
+            let cost = match best[w - a] {
+                Some(cost) => cost + c,
+                None => continue
+            };
+
+            let better = match best[w] {
+                Some(old) => cost > old || (
+                    options.tiebreak == TieBreak::MinL1 && cost == old && items[w - a] + 1 < items[w]
+                ),
+                None => true
+            };
+
+            if better {
+                best[w] = Some(cost);
+                item[w] = j;
+                items[w] = items[w - a] + 1;
+            }
+        }
+    }
+
+    log!(" -> Table filled, t={:?}", start.elapsed());
+
+    let cost = match best[capacity] {
+        Some(cost) => cost,
+        None => return Err(ILPError::NoSolution)
+    };
+
+    // items without weight can be added arbitrarily often
+    if weights.iter().zip(ilp.c.iter()).any(|(&a, &c)| a == 0 && c > 0) {
+        return Err(ILPError::Unbounded);
+    }
+
+    let mut x = Vector::zero(n);
+    let mut w = capacity;
+    while w > 0 {
+        let j = item[w];
+        x.data[j] += 1;
+        w -= weights[j];
+    }
+
+    log!(" -> Solution cost: {}", cost);
+    log!(" -> Done! Time elapsed: {:?}", start.elapsed());
+
+    Ok(x)
+}
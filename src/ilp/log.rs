@@ -0,0 +1,37 @@
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use ignore_result::Ignore;
+
+/*
+    The solvers report what they are doing with `log!` and `log_inline!`.
+    The log goes to stdout unless the solution itself is written to stdout
+    in a machine readable format, then it is sent to stderr.
+*/
+
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Sends the log to stderr instead of stdout.
+pub fn to_stderr(enabled:bool) {
+    TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+/// Used by the macros, partial lines are flushed immediately.
+pub fn write(args:fmt::Arguments, newline:bool) {
+    if TO_STDERR.load(Ordering::Relaxed) {
+        write_to(&mut io::stderr(), args, newline);
+    } else {
+        write_to(&mut io::stdout(), args, newline);
+    }
+}
+
+fn write_to(out:&mut dyn Write, args:fmt::Arguments, newline:bool) {
+    out.write_fmt(args).ignore();
+
+    if newline {
+        out.write_all(b"\n").ignore();
+    } else {
+        out.flush().ignore();
+    }
+}
@@ -1,171 +1,1661 @@
 use std::fmt::Display;
 use std::fmt;
 use std::slice::Iter;
+use std::convert::TryFrom;
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+/// Like `println!`, but for the log (see `log::to_stderr`).
+#[macro_export]
+macro_rules! log {
+    () => { $crate::ilp::log::write(format_args!(""), true) };
+    ($($arg:tt)*) => { $crate::ilp::log::write(format_args!($($arg)*), true) };
+}
+
+/// Like `print!` (but flushed), for the log.
+#[macro_export]
+macro_rules! log_inline {
+    ($($arg:tt)*) => { $crate::ilp::log::write(format_args!($($arg)*), false) };
+}
+
+pub mod log;
+pub mod parser;
+pub mod steinitz;
+pub mod discrepancy;
+pub mod reference;
+pub mod relaxation;
+pub mod knapsack;
+pub mod heuristics;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+mod graph;
+
+pub use graph::VectorDiGraph;
+
+pub type IntData = i32;
+pub type Cost = i32;
+pub type VarMapping = (String, usize);
+
+#[derive(Hash, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vector {
+    data: Vec<IntData>
+}
+
+/// Sparse representation of a vector: the non-zero entries as (index, value).
+#[derive(PartialEq, Eq, Clone)]
+pub struct SparseVector {
+    size: usize,
+    entries: Vec<(usize, IntData)>
+}
+
+/// Matrices with a lower density (fraction of non-zero entries)
+/// use sparse columns in the hot loops of the solvers.
+pub const SPARSE_DENSITY: f64 = 0.25;
+
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Matrix {
+    columns: Vec<Vector>,
+    size: (usize, usize) // rows, columns or (m,n)
+}
+
+#[allow(non_snake_case)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ILP {
+    pub A: Matrix,
+    pub b: Vector,
+    pub c: Vector,
+    /// Secondary objective (maximized), only breaks ties between the optima of c.
+    pub c2: Option<Vector>,
+    delta_A: IntData,
+    delta_b: IntData,
+    named_variables: Vec<VarMapping>,
+    upper_bounds: Vec<(String, IntData)>, // encoded as constraints
+    binaries: Vec<String>, // declared binary, their bound 1 is in upper_bounds
+    fixed_variables: Vec<(String, IntData)>, // removed from the ILP (fixed or by preprocessing)
+    slack_columns: Vec<Option<usize>>, // slack variable of every row that was an inequality
+    algorithm: Option<String>, // `solver:` directive of the file
+    objective_scale: IntData, // c is the objective of the model times this factor
+    objective_offset: Cost, // c*x of the fixed variables (in the units of c)
+    variable_map: VariableMap, // columns of the ILP before columns were removed
+    diagnostics: Vec<Diagnostic>, // of parsing and preprocessing
+    sense: Sense // of the model, c is always maximized
+}
+
+/// Severity of a `Diagnostic`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// The model was changed, e.g. preprocessing removed columns.
+    Info,
+    /// The model is probably not what was intended (or has no solution).
+    Warning
+}
+
+/// A message about the model from parsing, preprocessing or `ILP::check`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String
+}
+
+impl Diagnostic {
+    pub fn info(message:String) -> Self {
+        Diagnostic { severity: Severity::Info, message }
+    }
+
+    pub fn warning(message:String) -> Self {
+        Diagnostic { severity: Severity::Warning, message }
+    }
+
+    /// Prints the diagnostic to the log.
+    pub fn log(&self) {
+        match self.severity {
+            Severity::Info => log!(" -> {}", self.message),
+            Severity::Warning => log!(" -> Warning: {}", self.message)
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Info => write!(f, "info: {}", self.message),
+            Severity::Warning => write!(f, "warning: {}", self.message)
+        }
+    }
+}
+
+/// Relates the columns of an ILP to the columns of the ILP it was created
+/// from (before preprocessing or fixing variables removed columns).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableMap {
+    /// For every original column its index in the reduced ILP, or the
+    /// value it was fixed to if it was removed.
+    columns: Vec<Result<usize, IntData>>
+}
+
+impl VariableMap {
+    pub fn identity(n:usize) -> Self {
+        VariableMap { columns: (0..n).map(Ok).collect() }
+    }
+
+    /// Number of columns of the original ILP.
+    pub fn original_len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Index of the original column j in the reduced ILP (None if it was removed).
+    pub fn reduced_index(&self, j:usize) -> Option<usize> {
+        self.columns[j].ok()
+    }
+
+    /// Maps a solution of the reduced ILP to the columns of the original ILP.
+    pub fn map_back(&self, x:&Vector) -> Vector {
+        Vector {
+            data: self.columns.iter().map(|column| match *column {
+                Ok(j) => x.data[j],
+                Err(value) => value
+            }).collect()
+        }
+    }
+
+    /// Updates the map after columns of the reduced ILP were removed:
+    /// `new_idx[j]` is the new index of column j, None if it was removed
+    /// (its variable is fixed to 0).
+    fn select(&mut self, new_idx:&[Option<usize>]) {
+        for column in self.columns.iter_mut() {
+            if let Ok(j) = *column {
+                *column = new_idx[j].ok_or(0);
+            }
+        }
+    }
+}
+
+/// Objective sense of the model. Internally every ILP is a maximization
+/// problem, minimizing c*x is the same as maximizing -c*x.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sense {
+    Maximize,
+    Minimize
+}
+
+#[derive(Debug)]
+pub enum ILPError {
+    NoSolution,
+    Unbounded,
+    Timeout,
+    /// The memory limit (`SolverOptions::max_memory`) was reached.
+    ResourceExhausted,
+    /// Intermediate values exceeded the integer range, the result is unknown.
+    Overflow,
+    UnknownVariable(String),
+    /// The dimensions of A, b and c do not fit together (see `ILP::new`).
+    InvalidModel(&'static str)
+}
+
+impl Display for ILPError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ILPError::NoSolution => write!(f, "the ILP has no solution"),
+            ILPError::Unbounded => write!(f, "the ILP is unbounded"),
+            ILPError::Timeout => write!(f, "time limit reached, no solution found"),
+            ILPError::ResourceExhausted => write!(f, "memory limit reached, no solution found"),
+            ILPError::Overflow => write!(f, "integer overflow, the result is unknown"),
+            ILPError::UnknownVariable(name) => write!(f, "unknown variable {}", name),
+            ILPError::InvalidModel(reason) => write!(f, "invalid ILP: {}", reason)
+        }
+    }
+}
+
+impl std::error::Error for ILPError {}
+
+/// Outcome of a solver, more detailed than `Result<Vector, ILPError>`.
+/// The objective is c*x (maximized, see `Sense`).
+#[derive(Debug)]
+pub enum SolveResult {
+    Optimal { x: Vector, objective: Cost },
+    /// A solution that is not proven to be optimal (e.g. a time limit was reached).
+    Feasible { x: Vector, objective: Cost, proven_optimal: bool },
+    Infeasible,
+    /// `ray` is a direction y >= 0 with Ay = 0 and c*y > 0 (if known).
+    Unbounded { ray: Option<Vector> },
+    /// The solver stopped without a solution (time or memory limit).
+    Stopped(ILPError)
+}
+
+impl SolveResult {
+    /// Converts the result of a solver, the bool tells whether x is optimal.
+    pub fn new(ilp:&ILP, result:Result<(Vector, bool), ILPError>) -> Self {
+        match result {
+            Ok((x, true)) => SolveResult::Optimal { objective: ilp.c.dot(&x), x },
+            Ok((x, false)) => SolveResult::Feasible { objective: ilp.c.dot(&x), x, proven_optimal: false },
+            Err(ILPError::NoSolution) => SolveResult::Infeasible,
+            Err(ILPError::Unbounded) => SolveResult::Unbounded { ray: None },
+            Err(e) => SolveResult::Stopped(e)
+        }
+    }
+}
+
+impl From<SolveResult> for Result<Vector, ILPError> {
+    fn from(result:SolveResult) -> Self {
+        match result {
+            SolveResult::Optimal { x, .. } | SolveResult::Feasible { x, .. } => Ok(x),
+            SolveResult::Infeasible => Err(ILPError::NoSolution),
+            SolveResult::Unbounded { .. } => Err(ILPError::Unbounded),
+            SolveResult::Stopped(e) => Err(e)
+        }
+    }
+}
+
+/// Progress of a solver, passed to the `progress` callback of the solve functions.
+pub enum ProgressEvent {
+    /// The graph construction finished a depth, `surface` new vertices were added.
+    GraphDepth { depth: usize, surface: usize },
+    /// The Bellman-Ford algorithm finished its n-th iteration.
+    BellmanFordIter { n: usize },
+    /// The lookup table of the discrepancy algorithm has `size` entries.
+    TableSize { size: usize },
+    /// A (better) solution for b with the given cost was found, it is
+    /// not necessarily optimal until the solver finishes.
+    Incumbent { cost: Cost }
+}
+
+/// Which of several optimal solutions the solvers return.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// The first one that is found.
+    First,
+    /// One with the smallest 1-norm (the fewest items).
+    MinL1,
+    /// One with the fewest non-zero variables (Jansen & Rohwedder only,
+    /// the other algorithms return the first one).
+    MinSupport
+}
+
+/// Settings for the solvers, not every setting is used by every algorithm.
+pub struct SolverOptions {
+    /// Multiplies the radius of the search region of the Eisenbrand &
+    /// Weismantel algorithm. Values below 1 shrink the graph but break the
+    /// correctness guarantee: the optimum (or any solution) might be missed.
+    pub bound_factor: f32,
+    /// Limits the number of Bellman-Ford iterations of the Eisenbrand &
+    /// Weismantel algorithm. If the limit is reached before convergence the
+    /// returned solution is feasible but not necessarily optimal.
+    pub max_bf_iterations: Option<usize>,
+    /// Initial node capacity of the graph of the Eisenbrand & Weismantel
+    /// algorithm, estimated from the instance if not set.
+    pub node_capacity: Option<usize>,
+    /// Narrates the steps of the algorithms.
+    pub explain: bool,
+    /// Stops the search after this time. If a solution is known at that
+    /// point it is returned (without a proof of optimality).
+    pub timeout: Option<Duration>,
+    pub tiebreak: TieBreak,
+    /// Caps the bound on ‖x‖₁ of the table entries of the Jansen & Rohwedder
+    /// algorithm (which otherwise grows by 1.2 per iteration). A cap below
+    /// the 1-norm of the optimal solutions makes the algorithm miss them.
+    pub max_var_value: Option<IntData>,
+    /// Prints the vector and cost of every graph node after the longest
+    /// path search of the Eisenbrand & Weismantel algorithm (small graphs).
+    pub dump_costs: bool,
+    /// Soft limit (in bytes) for the estimated memory of the graph of the
+    /// Eisenbrand & Weismantel algorithm, checked after every depth.
+    pub max_memory: Option<usize>
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        SolverOptions {
+            bound_factor: 1.0,
+            max_bf_iterations: None,
+            node_capacity: None,
+            explain: false,
+            timeout: None,
+            tiebreak: TieBreak::First,
+            max_var_value: None,
+            dump_costs: false,
+            max_memory: None
+        }
+    }
+}
+
+/// Names of the preprocessing passes in the order they are applied.
+pub const PREPROCESS_PASSES: [&str; 4] = ["row-gcd", "duplicate-rows", "zero-columns", "simplify"];
+
+/// Enabled passes of `ILP::preprocess`.
+pub struct PreprocessOptions {
+    /// Divides every row (and b) by the gcd of its coefficients.
+    pub row_gcd: bool,
+    /// Removes rows that are equal to another row.
+    pub duplicate_rows: bool,
+    /// Removes zero columns that can not improve the objective.
+    pub zero_columns: bool,
+    /// Removes duplicate and dominated columns, see `ILP::remove_dominated_columns`.
+    pub simplify: bool
+}
+
+impl Default for PreprocessOptions {
+    fn default() -> Self {
+        PreprocessOptions {
+            row_gcd: true,
+            duplicate_rows: true,
+            zero_columns: true,
+            simplify: true
+        }
+    }
+}
+
+impl PreprocessOptions {
+    /// Disables a pass by its name, see `PREPROCESS_PASSES`.
+    pub fn disable(&mut self, pass:&str) {
+        match pass {
+            "row-gcd"        => self.row_gcd = false,
+            "duplicate-rows" => self.duplicate_rows = false,
+            "zero-columns"   => self.zero_columns = false,
+            "simplify"       => self.simplify = false,
+            _                => panic!("unknown preprocessing pass {}", pass)
+        }
+    }
+}
+
+impl SolverOptions {
+    pub fn time_limit_reached(&self, start:Instant) -> bool {
+        match self.timeout {
+            Some(timeout) => start.elapsed() >= timeout,
+            None => false
+        }
+    }
+}
+
+impl ILP {
+    /// Returns `InvalidModel` if A has no rows or columns, b or c do not
+    /// match its size or an entry is `IntData::MIN` (its absolute value overflows).
+    pub fn new(mat:Matrix, b:Vector, c:Vector) -> Result<Self, ILPError> {
+        ILP::with_named_vars(mat, b, c, Vec::new())
+    }
+
+    /// Like `new`, the names have to be non-empty and refer to columns of A.
+    pub fn with_named_vars(mat:Matrix, b:Vector, c:Vector, vars:Vec<VarMapping>) -> Result<Self, ILPError> {
+        if mat.size.0 == 0 || mat.size.1 == 0 {
+            return Err(ILPError::InvalidModel("A has no rows or no columns"));
+        }
+        if b.len() != mat.size.0 {
+            return Err(ILPError::InvalidModel("b needs an entry for every row of A"));
+        }
+        if c.len() != mat.size.1 {
+            return Err(ILPError::InvalidModel("c needs an entry for every column of A"));
+        }
+        if mat.iter().flat_map(|column| column.iter()).chain(b.iter()).any(|&x| x == IntData::MIN) {
+            return Err(ILPError::InvalidModel("an entry of A or b is out of range"));
+        }
+        if vars.iter().any(|(s, idx)| s.is_empty() || *idx >= c.len()) {
+            return Err(ILPError::InvalidModel("a variable has no name or no column"));
+        }
+
+        Ok(ILP::from_parts(mat, b, c, vars))
+    }
+
+    /// Builds the ILP without checks, for parts taken from a valid ILP.
+    fn from_parts(mat:Matrix, b:Vector, c:Vector, vars:Vec<VarMapping>) -> Self {
+        let (m, n) = mat.size;
+        let da = mat.max_abs_entry();
+        let db = b.inf_norm();
+
+        let mut variables = vars;
+        variables.sort_by(|a,b| a.1.cmp(&b.1));
+    
+        ILP {
+            A: mat,
+            b: b,
+            c: c,
+            c2: None,
+            delta_A: da,
+            delta_b: db,
+            named_variables: variables,
+            upper_bounds: Vec::new(),
+            binaries: Vec::new(),
+            fixed_variables: Vec::new(),
+            slack_columns: vec![None; m],
+            algorithm: None,
+            objective_scale: 1,
+            objective_offset: 0,
+            variable_map: VariableMap::identity(n),
+            diagnostics: Vec::new(),
+            sense: Sense::Maximize
+        }
+    }
+
+    /// Replaces the objective (maximized like `c`), everything else is kept.
+    /// The cost of fixed variables is dropped, c does not cover them.
+    pub fn with_cost(mut self, c:Vector) -> Self {
+        assert_eq!(c.len(), self.A.size.1, "c has to have an entry for every column");
+        self.c = c;
+        self.objective_scale = 1;
+        self.objective_offset = 0;
+        self
+    }
+
+    pub fn sense(&self) -> Sense {
+        self.sense
+    }
+
+    /// Changes the objective sense of the model (negates c if it changes).
+    pub fn set_sense(&mut self, sense:Sense) {
+        if sense != self.sense {
+            self.c = self.c.scale(-1);
+            self.objective_offset = -self.objective_offset;
+            self.sense = sense;
+        }
+    }
+
+    /// Multiplies c by a positive factor, e.g. to make fractional costs
+    /// integral. The factor is recorded in `objective_scale`.
+    pub fn scale_objective(&mut self, factor:IntData) {
+        assert!(factor > 0, "the scale factor has to be positive");
+        self.c = self.c.scale(factor);
+        self.objective_scale *= factor;
+        self.objective_offset *= factor;
+    }
+
+    /// The factor c was scaled by, see `scale_objective`.
+    pub fn objective_scale(&self) -> IntData {
+        self.objective_scale
+    }
+
+    /// The objective value of x in the units (and sense) of the model,
+    /// including the cost of the fixed variables (see `fix_variable`).
+    pub fn objective_value(&self, x:&Vector) -> f64 {
+        let value = match self.sense {
+            Sense::Maximize => self.c.dot(x) + self.objective_offset,
+            Sense::Minimize => -(self.c.dot(x) + self.objective_offset)
+        };
+
+        value as f64 / self.objective_scale as f64
+    }
+
+    /// The algorithm selected by the `solver:` directive of the file (if any).
+    pub fn algorithm(&self) -> Option<&str> {
+        self.algorithm.as_deref()
+    }
+
+    /// Largest absolute value of the entries of A.
+    #[allow(non_snake_case)]
+    pub fn delta_A(&self) -> IntData {
+        self.delta_A
+    }
+
+    /// Largest absolute value of the entries of b.
+    pub fn delta_b(&self) -> IntData {
+        self.delta_b
+    }
+
+    /// Recomputes Δ and ‖b‖∞ after A or b have been modified.
+    fn update_deltas(&mut self) {
+        self.delta_A = self.A.max_abs_entry();
+        self.delta_b = self.b.inf_norm();
+    }
+
+    pub fn print_details(&self) {
+        log!("ILP details:");
+        log!(" -> constraints: {}", self.A.size.0);
+        log!(" -> variables: {:3}", self.A.size.1);
+        let list = self.variable_names();
+        log_inline!("    {:?}", list);
+        let slacks = self.A.size.1 - list.len();
+        if slacks > 0 {
+            log!(" + {} slack variables", slacks);
+        } else {
+            log!();
+        }
+        if !self.binaries.is_empty() {
+            log!("    binary: {:?}", self.binaries);
+        }
+        let bounds:Vec<String> = self.upper_bounds.iter()
+            .filter(|(s, _)| !self.binaries.contains(s))
+            .map(|(s, bound)| format!("{} <= {}", s, bound))
+            .collect();
+        if !bounds.is_empty() {
+            log!("    bounds: {}", bounds.join(", "));
+        }
+        let opposite = self.A.opposite_column_pairs();
+        if !opposite.is_empty() {
+            let names = self.column_names();
+            let pairs:Vec<String> = opposite.iter()
+                .map(|&(i, j)| format!("({}, {})", names[i], names[j]))
+                .collect();
+            log!("    opposite columns: {}", pairs.join(", "));
+        }
+        log!(" -> \u{0394}    = {}", self.delta_A);
+        let histogram:Vec<String> = self.coefficient_histogram().iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(value, count)| format!("{}: {}", value, count))
+            .collect();
+        log!("    |a_ij| counts: {}", histogram.join(", "));
+        log!(" -> \u{2016}b\u{2016}\u{221E} = {}", self.delta_b);
+        if self.A.size.0 > 1 {
+            log!(" -> Matrix A:\n{}", self.A);
+        } else {
+            log_inline!(  " -> Matrix A: {}", self.A);
+        }
+        log!(" -> b = {:?}", self.b);
+        log!(" -> c = {:?}", self.c);
+        if let Some(c2) = &self.c2 {
+            log!(" -> c2 = {:?} (secondary)", c2);
+        }
+        if let Some(bound) = self.objective_upper_bound() {
+            log!(" -> c*x <= {}", bound);
+        }
+        for diagnostic in self.check() {
+            diagnostic.log();
+        }
+        log!();
+    }
+
+    /// Warnings about the model: redundant or contradicting constraints,
+    /// slack variables with a cost and b outside of the lattice of A.
+    pub fn check(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        let (m, _) = self.A.size;
+        match self.A.rank() {
+            Some(rank) if rank < m => {
+                diagnostics.push(Diagnostic::warning(format!(
+                    "A has rank {} < {} constraints; {} may be redundant.", rank, m, m - rank
+                )));
+
+                let mut augmented = self.A.clone();
+                augmented.columns.push(self.b.clone());
+                augmented.size.1 += 1;
+                if matches!(augmented.rank(), Some(r) if r > rank) {
+                    diagnostics.push(Diagnostic::warning(
+                        "the dependent constraints are contradicting, Ax=b has no solution.".to_string()
+                    ));
+                }
+            },
+            Some(_) => {},
+            None => diagnostics.push(Diagnostic::info(
+                "the rank check was skipped, intermediate values are too large.".to_string()
+            ))
+        }
+        for (i, c) in self.costly_slack_variables() {
+            diagnostics.push(Diagnostic::warning(format!(
+                "the slack variable of row {} has cost {} (instead of 0).", i+1, c
+            )));
+        }
+        match self.b_in_lattice() {
+            Ok(true) => {},
+            Ok(false) => diagnostics.push(Diagnostic::warning(
+                "b is not in the lattice spanned by the columns of A, the ILP has no solution.".to_string()
+            )),
+            Err(_) => diagnostics.push(Diagnostic::info(
+                "the lattice check was skipped, intermediate values are too large.".to_string()
+            ))
+        }
+
+        diagnostics
+    }
+
+    /// The diagnostics of parsing and preprocessing, in order.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// The variables declared binary, without those that were fixed.
+    pub fn binaries(&self) -> &[String] {
+        &self.binaries
+    }
+
+    /// Prints the diagnostic and keeps it for `diagnostics`.
+    fn diagnose(&mut self, diagnostic:Diagnostic) {
+        diagnostic.log();
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Prints the `count` columns with the highest `column_efficiency`.
+    pub fn print_column_efficiency(&self, count:usize) {
+        let names = self.column_names();
+        let mut columns:Vec<(usize, f32)> = self.column_efficiency().into_iter().enumerate().collect();
+        columns.sort_by(|(_, e1), (_, e2)| e2.partial_cmp(e1).unwrap());
+
+        let list:Vec<String> = columns.iter()
+            .take(count)
+            .map(|&(j, e)| format!("{}: {:.2}", names[j], e))
+            .collect();
+        log!(" -> most efficient columns (c_j / \u{2016}a_j\u{2016}\u{2081}): {}", list.join(", "));
+    }
+
+    /// Decides if Ax=b has a solution x >= 0 for a non-negative A, i.e. if b is
+    /// in the monoid generated by the columns. Searches all sums of columns
+    /// v <= b (componentwise), thus only suited for small b.
+    /// Returns None if A has negative entries.
+    pub fn is_feasible_monoid(&self) -> Option<bool> {
+        if !self.A.non_negative() {
+            return None;
+        }
+        if !self.b.non_negative() {
+            return Some(false);
+        }
+
+        let columns:Vec<&Vector> = self.A.iter().filter(|column| !column.is_zero()).collect();
+        let mut visited = hashbrown::HashSet::new();
+        let mut stack = vec![Vector::zero(self.b.len())];
+
+        while let Some(v) = stack.pop() {
+            if v == self.b {
+                return Some(true);
+            }
+
+            for column in columns.iter() {
+                let w = v.add(column);
+
+                if w.iter().zip(self.b.iter()).all(|(x, b)| x <= b) && !visited.contains(&w) {
+                    visited.insert(w.clone());
+                    stack.push(w);
+                }
+            }
+        }
+
+        Some(false)
+    }
+
+    /// True if b is in the cone spanned by the columns of A (Ax = b has a
+    /// real solution x >= 0). If not, the ILP has no solution.
+    pub fn in_conic_hull(&self) -> bool {
+        relaxation::is_feasible(self)
+    }
+
+    /// An integer solution of Ax=b ignoring x >= 0 (entries may be negative),
+    /// or None if b is not in the lattice generated by the columns of A.
+    /// `Overflow` is returned if intermediate values or the entries of x
+    /// get too large (b may or may not be in the lattice then).
+    pub fn integer_feasible_ignoring_signs(&self) -> Result<Option<Vector>, ILPError> {
+        let (u, y) = match self.hermite_coordinates(true)? {
+            Some(uy) => uy,
+            None => return Ok(None)
+        };
+
+        let mut data = Vec::with_capacity(y.len());
+        for k in 0..y.len() {
+            let x = checked_dot(u.iter().map(|column| column[k]), &y).ok_or(ILPError::Overflow)?;
+            data.push(IntData::try_from(x).map_err(|_| ILPError::Overflow)?);
+        }
+        let x = Vector { data };
+        debug_assert!((0..self.A.size.0).all(|i| self.A.row(i).dot(&x) == self.b.data[i]), "x does not solve Ax=b");
+
+        Ok(Some(x))
+    }
+
+    /// True if b is in the lattice generated by the columns of A, i.e. Ax=b
+    /// has an integer solution if x >= 0 is ignored (`Overflow` if unknown).
+    pub fn b_in_lattice(&self) -> Result<bool, ILPError> {
+        self.hermite_coordinates(false).map(|uy| uy.is_some())
+    }
+
+    /// A is brought into Hermite normal form with unimodular column
+    /// operations AU = [H 0], then Hy = b is solved. Returns the columns
+    /// of U and y (x = Uy solves Ax=b), or None if there is no integer y.
+    /// All arithmetic is checked, `Overflow` is returned if the i128
+    /// range is exceeded. U is only computed if `track_u` is set (the
+    /// columns are empty otherwise), its entries grow much faster than H.
+    fn hermite_coordinates(&self, track_u:bool) -> Result<Option<(WideColumns, Vec<i128>)>, ILPError> {
+        let (m, n) = self.A.size;
+        let mut h:WideColumns = self.A.iter()
+            .map(|column| column.iter().map(|&a| a as i128).collect())
+            .collect();
+        let mut u:WideColumns = (0..n)
+            .map(|j| if track_u { (0..n).map(|k| if j == k { 1 } else { 0 }).collect() } else { Vec::new() })
+            .collect();
+        let mut pivots = Vec::with_capacity(m); // (row, column)
+
+        for i in 0..m {
+            let r = pivots.len();
+            if r == n {
+                break;
+            }
+
+            // eliminate h[j][i] for j > r with the euclidean algorithm: the column
+            // with the smallest non-zero entry is the pivot, the others are reduced
+            // by (rounded) multiples of it
+            loop {
+                let pivot = (r..n).filter(|&j| h[j][i] != 0).min_by_key(|&j| h[j][i].abs());
+                let k = match pivot {
+                    Some(k) => k,
+                    None => break
+                };
+                h.swap(r, k);
+                u.swap(r, k);
+
+                let mut reduced = true;
+                for j in r+1..n {
+                    if h[j][i] != 0 {
+                        let q = round_div(h[j][i], h[r][i]);
+                        subtract_column(&mut h, j, r, q)?;
+                        subtract_column(&mut u, j, r, q)?;
+                        reduced &= h[j][i] == 0;
+                    }
+                }
+
+                if reduced {
+                    break;
+                }
+            }
+
+            if h[r][i] != 0 {
+                // reduce the entries left of the pivot, otherwise y grows quickly
+                for k in 0..r {
+                    let q = round_div(h[k][i], h[r][i]);
+                    if q != 0 {
+                        subtract_column(&mut h, k, r, q)?;
+                        subtract_column(&mut u, k, r, q)?;
+                    }
+                }
+                pivots.push((i, r));
+            }
+        }
+
+        // forward substitution, rows without pivot have to be satisfied already
+        let mut y = vec![0; n];
+        let mut pivot_iter = pivots.iter().peekable();
+        for i in 0..m {
+            let sum = checked_dot(h.iter().map(|column| column[i]), &y).ok_or(ILPError::Overflow)?;
+            let rest = (self.b.data[i] as i128).checked_sub(sum).ok_or(ILPError::Overflow)?;
+
+            match pivot_iter.peek() {
+                Some(&&(row, r)) if row == i => {
+                    if rest.checked_rem(h[r][i]).ok_or(ILPError::Overflow)? != 0 {
+                        return Ok(None);
+                    }
+                    y[r] = rest.checked_div(h[r][i]).ok_or(ILPError::Overflow)?;
+                    pivot_iter.next();
+                },
+                _ => if rest != 0 {
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(Some((u, y)))
+    }
+
+    pub fn print_solution(&self, x:&Vector) {
+        if self.named_variables.is_empty() && self.fixed_variables.is_empty() {
+            log!(" x={:?}", x);
+        } else {
+            for (name, idx) in self.named_variables.iter() {
+                log!(" {} = {}", name, x.data[*idx]);
+            }
+        }
+
+        // variables that were removed from the ILP
+        for (name, value) in self.fixed_variables.iter() {
+            log!(" {} = {}", name, value);
+        }
+    }
+
+    /// Prints the solution as `name=value` lines to stdout (not to the log),
+    /// one per variable of the model (slack variables are omitted).
+    pub fn print_assignment(&self, x:&Vector) {
+        if self.named_variables.is_empty() && self.fixed_variables.is_empty() {
+            for (j, value) in x.iter().enumerate() {
+                println!("x{}={}", j + 1, value);
+            }
+        } else {
+            for (name, idx) in self.named_variables.iter() {
+                println!("{}={}", name, x.data[*idx]);
+            }
+        }
+
+        for (name, value) in self.fixed_variables.iter() {
+            println!("{}={}", name, value);
+        }
+    }
+
+    /// Distribution of the absolute values of the entries of A:
+    /// the k-th entry is the number of coefficients with |a_ij| = k.
+    pub fn coefficient_histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![0; self.delta_A as usize + 1];
+
+        for column in self.A.iter() {
+            for &a in column.iter() {
+                let k = a.unsigned_abs() as usize;
+                if k >= histogram.len() {
+                    histogram.resize(k + 1, 0);
+                }
+                histogram[k] += 1;
+            }
+        }
+
+        histogram
+    }
+
+    /// Bound on the support (number of non-zero variables) of an optimal
+    /// solution: there is one with support <= 2m * log2(4mΔ) [Eisenbrand & Shmonin].
+    pub fn support_bound(&self) -> f64 {
+        let m = self.A.size.0 as f64;
+        let delta = IntData::max(self.delta_A, 1) as f64;
+
+        2.0 * m * f64::log2(4.0 * m * delta)
+    }
+
+    /// The columns used by the solution `x` (as a sum of columns of A),
+    /// i.e. `(column index, multiplicity)` for every non-zero entry.
+    pub fn solution_support(&self, x:&Vector) -> Vec<(usize, IntData)> {
+        assert_eq!(x.data.len(), self.A.size.1, "x has the wrong dimension");
+
+        x.iter().enumerate()
+            .filter(|&(_, &v)| v != 0)
+            .map(|(j, &v)| (j, v))
+            .collect()
+    }
+
+    /// A hash of A, b, c (and c2) and the objective sense that is stable across
+    /// runs and platforms (64 bit FNV-1a), e.g. as a key for caching results.
+    /// Variable names do not change the hash.
+    pub fn content_hash(&self) -> u64 {
+        let mut hash:u64 = 0xcbf2_9ce4_8422_2325;
+        let mut write = |x:i64| {
+            for byte in x.to_le_bytes().iter() {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+
+        let (m, n) = self.A.size;
+        write(m as i64);
+        write(n as i64);
+        for column in self.A.iter() {
+            column.iter().for_each(|&a| write(a as i64));
+        }
+        self.b.iter().for_each(|&b| write(b as i64));
+        self.c.iter().for_each(|&c| write(c as i64));
+        if let Some(c2) = &self.c2 {
+            c2.iter().for_each(|&c| write(c as i64));
+        }
+        write(if self.sense == Sense::Maximize { 1 } else { -1 });
+
+        hash
+    }
+
+    /// The value of the slack variable of every constraint for the solution x,
+    /// i.e. how far the constraint is from being tight (0 for equations).
+    pub fn constraint_slacks(&self, x:&Vector) -> Vec<IntData> {
+        assert_eq!(x.len(), self.A.size.1, "x has the wrong dimension");
+
+        self.slack_columns.iter()
+            .map(|j| j.map_or(0, |j| x.data[j]))
+            .collect()
+    }
+
+    /// The range [min, max] of c*x over the LP relaxation (rounded inwards
+    /// to integers), it contains c*x of every integer solution x. None if
+    /// the relaxation is infeasible or unbounded in either direction.
+    pub fn lattice_width_c(&self) -> Option<(Cost, Cost)> {
+        let max = relaxation::solve(self).ok()?.objective;
+
+        let mut negated = self.clone();
+        negated.c = Vector { data: self.c.iter().map(|&c| -c).collect() };
+        let min = -relaxation::solve(&negated).ok()?.objective;
+
+        Some(((min - 1e-6).ceil() as Cost, (max + 1e-6).floor() as Cost))
+    }
+
+    /// The rows whose slack variable has a non-zero cost, together with
+    /// that cost. The parser adds slacks with cost 0, a cost changes the
+    /// objective of the model.
+    pub fn costly_slack_variables(&self) -> Vec<(usize, Cost)> {
+        self.slack_columns.iter()
+            .enumerate()
+            .filter_map(|(i, j)| j.map(|j| (i, self.c.data[j])))
+            .filter(|&(_, c)| c != 0)
+            .collect()
+    }
+
+    /// How the columns of this ILP relate to the columns before preprocessing
+    /// (or fixing variables) removed some of them.
+    pub fn variable_map(&self) -> &VariableMap {
+        &self.variable_map
+    }
+
+    /// Maps a solution of this (reduced) ILP back to the columns of the
+    /// ILP before columns were removed, see `variable_map`.
+    pub fn map_solution_back(&self, x:&Vector) -> Vector {
+        assert_eq!(x.len(), self.A.size.1, "x has the wrong dimension");
+        self.variable_map.map_back(x)
+    }
+
+    /// Number of slack variables the parser added for inequalities and bounds.
+    pub fn num_slack_variables(&self) -> usize {
+        self.slack_columns.iter().flatten().count()
+    }
+
+    /// A simple upper bound for the objective of any solution.
+    /// If all entries of a row of A have the same sign (say a_i >= 0) and
+    /// c_j <= l * a_ij for all j, then c*x <= l * a_i*x = l * b_i.
+    /// The smallest such bound over all rows is returned, None if no row
+    /// can be used.
+    pub fn objective_upper_bound(&self) -> Option<Cost> {
+        let (m,_) = self.A.size;
+        let mut bound:Option<Cost> = None;
+
+        for i in 0..m {
+            let row:Vec<i64> = self.A.iter().map(|col| col.data[i] as i64).collect();
+            let sign = if row.iter().all(|&a| a >= 0) {
+                1
+            } else if row.iter().all(|&a| a <= 0) {
+                -1
+            } else {
+                continue;
+            };
+
+            // l = p/q = max c_j / a_ij (q > 0)
+            let mut ratio:Option<(i64, i64)> = None;
+            let mut usable = true;
+            for (&a, &c) in row.iter().zip(self.c.iter()) {
+                let (a, c) = (sign * a, c as i64);
+
+                if a == 0 {
+                    // the variable is not restricted by this row
+                    usable &= c <= 0;
+                } else {
+                    let larger = match ratio {
+                        Some((p,q)) => c*q > p*a,
+                        None => true
+                    };
+
+                    if larger {
+                        ratio = Some((c, a));
+                    }
+                }
+            }
+
+            if let (true, Some((p,q))) = (usable, ratio) {
+                let row_bound = (p * sign * self.b.data[i] as i64).div_euclid(q);
+
+                if row_bound >= Cost::MIN as i64 && row_bound <= Cost::MAX as i64 {
+                    let row_bound = row_bound as Cost;
+                    bound = Some(bound.map_or(row_bound, |b| Cost::min(b, row_bound)));
+                }
+            }
+        }
+
+        bound
+    }
+
+    /// Picks the algorithm that is expected to be faster on this instance
+    /// by comparing the estimated graph size of the Eisenbrand & Weismantel
+    /// algorithm with the estimated lookup table size of the Jansen &
+    /// Rohwedder algorithm. Knapsack problems are solved with dynamic
+    /// programming. Returns the algorithm and a short explanation.
+    pub fn recommend_algorithm(&self) -> (&'static str, String) {
+        if knapsack::applies(self) {
+            return ("kp", "a single constraint with non-negative coefficients (knapsack problem)".to_string());
+        }
+
+        let graph = steinitz::estimate_graph_size(self);
+        let table = discrepancy::estimate_table_size(self) as f64;
+        let k = discrepancy::compute_K(self);
+
+        if table < graph {
+            ("jr", format!("K={}, estimated table size {:.1e} < estimated graph size {:.1e}", k, table, graph))
+        } else {
+            ("ew", format!("estimated graph size {:.1e} <= estimated table size {:.1e} (K={})", graph, table, k))
+        }
+    }
+
+    /// Writes the model in the .ilp file format: with its objective sense
+    /// (and scale), inequalities instead of slack variables and the columns
+    /// in the same order, thus re-parsing the string yields the same model.
+    /// Slack variables with a cost (or that were changed otherwise) are
+    /// written out as regular variables. Fixed variables are not included.
+    pub fn to_ilp_string(&self) -> String {
+        let names = self.column_names();
+        let (m,n) = self.A.size;
+
+        // slack columns that can be written as the inequality of their row
+        let mut inequality = vec![None; m]; // "<=" or ">="
+        let mut hidden = vec![false; n];
+        for (i, slack) in self.slack_columns.iter().enumerate() {
+            if let Some(j) = *slack {
+                let column = &self.A.columns[j];
+                let cost2 = self.c2.as_ref().map_or(0, |c2| c2.data[j]);
+                if self.c.data[j] == 0 && cost2 == 0 && column.nnz() == 1 && column.data[i].abs() == 1 {
+                    inequality[i] = Some(if column.data[i] == 1 { "<=" } else { ">=" });
+                    hidden[j] = true;
+                }
+            }
+        }
+        let columns:Vec<usize> = (0..n).filter(|&j| !hidden[j]).collect();
+
+        // every column appears in the objective so that the parser
+        // assigns the same column indices when reading the model again
+        let sign = if self.sense == Sense::Maximize { 1 } else { -1 };
+        let terms:Vec<String> = columns.iter()
+            .map(|&j| {
+                debug_assert_eq!(self.c.data[j] % self.objective_scale, 0);
+                format!("{}*{}", sign * self.c.data[j] / self.objective_scale, names[j])
+            })
+            .collect();
+        let mode = if self.sense == Sense::Maximize { "maximize" } else { "minimize" };
+        let mut str = format!("{}:\n    {}", mode, terms.join(" + "));
+
+        if let Some(c2) = &self.c2 {
+            let terms:Vec<String> = columns.iter()
+                .map(|&j| format!("{}*{}", c2.data[j], names[j]))
+                .collect();
+            str.push_str(&format!("\nthen maximize:\n    {}", terms.join(" + ")));
+        }
+
+        str.push_str("\nsubject to:\n");
+        for (i, relation) in inequality.iter().enumerate() {
+            let terms:Vec<String> = columns.iter()
+                .filter(|&&j| self.A.columns[j].data[i] != 0)
+                .map(|&j| match self.A.columns[j].data[i] {
+                    1  => names[j].clone(),
+                    -1 => format!("-{}", names[j]),
+                    a  => format!("{}*{}", a, names[j])
+                })
+                .collect();
+
+            let left = if terms.is_empty() { "0".to_string() } else { terms.join(" + ") };
+            str.push_str(&format!("    {} {} {}\n", left, relation.unwrap_or("="), self.b.data[i]));
+        }
+
+        if let Some(algorithm) = &self.algorithm {
+            str.push_str(&format!("solver: {}\n", algorithm));
+        }
+
+        str
+    }
+
+    /// Names of the (structural) variables in column order, without the
+    /// slack variables and the variables that were removed from the ILP.
+    pub fn variable_names(&self) -> Vec<&str> {
+        // named_variables is sorted by the column index
+        self.named_variables.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Variable names in column order, unnamed (slack) columns get a
+    /// generated name that does not collide with the named variables.
+    fn column_names(&self) -> Vec<String> {
+        let mut names:Vec<Option<String>> = vec![None; self.A.size.1];
+        for (name, idx) in self.named_variables.iter() {
+            names[*idx] = Some(name.clone());
+        }
+
+        let mut prefix = "s".to_string();
+        while self.named_variables.iter().any(|(name, _)| name.starts_with(&prefix)) {
+            prefix.push('s');
+        }
+
+        names.into_iter()
+            .enumerate()
+            .map(|(j, o)| o.unwrap_or_else(|| format!("{}{}", prefix, j+1)))
+            .collect()
+    }
+
+    /// The cost per unit of "weight" of every column: c_j / ‖a_j‖₁. Zero
+    /// columns get ±infinity (or 0 if c_j = 0).
+    pub fn column_efficiency(&self) -> Vec<f32> {
+        self.A.iter().zip(self.c.iter())
+            .map(|(column, &c)| match column.one_norm() {
+                0 if c == 0 => 0.0,
+                0 => c.signum() as f32 * f32::INFINITY,
+                norm => c as f32 / norm as f32
+            })
+            .collect()
+    }
+
+    /// The (primary, secondary) cost of column j, compared lexicographically.
+    pub fn cost_key(&self, j:usize) -> (Cost, Cost) {
+        (self.c.data[j], self.c2.as_ref().map_or(0, |c2| c2.data[j]))
+    }
+
+    /// Removes duplicate columns, only the column with the highest cost is kept.
+    /// The variables of the removed columns are fixed to 0.
+    pub fn simplify(mut self) -> Self {
+        let mut keep = vec![true; self.A.size.1];
+
+        for (i, column) in self.A.iter().enumerate() {
+            if !keep[i] {
+                continue;
+            }
+
+            let mut best = i;
+            for (j, other) in self.A.iter().enumerate().skip(i+1) {
+                if keep[j] && column == other {
+                    // keep column with highest cost/weight
+                    if self.cost_key(j) > self.cost_key(best) {
+                        keep[best] = false;
+                        best = j;
+                    } else {
+                        keep[j] = false;
+                    }
+                }
+            }
+        }
+
+        let removed = keep.iter().filter(|&&k| !k).count();
+        self.diagnose(Diagnostic::info(format!("Removed {} column(s).", removed)));
+
+        self.select_columns(&keep)
+    }
+
+    /// Removes columns j that are a multiple k >= 1 of another column i with
+    /// c_j <= k * c_i: every use of j can be replaced by k uses of i (the same
+    /// Ax, no worse cost). This includes duplicate columns (k = 1) of which
+    /// only one is kept. The variables of the removed columns are fixed to 0.
+    pub fn remove_dominated_columns(mut self) -> Self {
+        let n = self.A.size.1;
+        let mut keep = vec![true; n];
+
+        for i in 0..n {
+            for j in 0..n {
+                if i == j || !keep[i] || !keep[j] {
+                    continue;
+                }
+
+                let k = match self.A.columns[j].multiple_of(&self.A.columns[i]) {
+                    Some(k) if k >= 1 => k,
+                    _ => continue
+                };
+
+                let (c_i, c2_i) = self.cost_key(i);
+                let dominated = match self.cost_key(j).cmp(&(k * c_i, k * c2_i)) {
+                    Ordering::Less => true,
+                    Ordering::Equal => k > 1 || j > i, // of equal duplicates the first is kept
+                    Ordering::Greater => false
+                };
+
+                if dominated {
+                    keep[j] = false;
+                }
+            }
+        }
+
+        let removed = keep.iter().filter(|&&k| !k).count();
+        if removed == 0 {
+            return self;
+        }
+
+        self.diagnose(Diagnostic::info(format!("Removed {} dominated column(s).", removed)));
+
+        self.select_columns(&keep)
+    }
+
+    /// Runs the enabled preprocessing passes (in the order of `PREPROCESS_PASSES`).
+    /// Returns `NoSolution` if a pass proves that the ILP is infeasible.
+    pub fn preprocess(self, options:PreprocessOptions) -> Result<Self, ILPError> {
+        log!("Preprocessing...");
+        let mut ilp = self;
+
+        if options.row_gcd {
+            ilp = ilp.reduce_row_gcd()?;
+        }
+
+        if options.duplicate_rows {
+            ilp = ilp.remove_duplicate_rows()?;
+        }
+
+        if options.zero_columns {
+            ilp = ilp.remove_zero_columns();
+        }
+
+        if options.simplify {
+            ilp = ilp.remove_dominated_columns();
+        }
+
+        // every pass should keep them up to date, but the solvers rely on them
+        ilp.update_deltas();
+
+        log!();
+
+        Ok(ilp)
+    }
+
+    /// Divides every row by the gcd of its coefficients. If the gcd
+    /// does not divide the corresponding entry of b there is no solution.
+    pub fn reduce_row_gcd(mut self) -> Result<Self, ILPError> {
+        let mut reduced = 0;
+
+        for (i, b) in self.b.data.iter_mut().enumerate() {
+            let g = self.A.iter().fold(0, |g, column| gcd(g, column.data[i]));
+
+            if g == 0 {
+                // zero row, handled by the solvers
+                continue;
+            }
+
+            if *b % g != 0 {
+                log!(" -> The gcd {} of row {} does not divide {}.", g, i+1, b);
+                return Err(ILPError::NoSolution);
+            }
+
+            if g > 1 {
+                for column in self.A.columns.iter_mut() {
+                    column.data[i] /= g;
+                }
+                *b /= g;
+                reduced += 1;
+            }
+        }
+
+        if reduced > 0 {
+            self.diagnose(Diagnostic::info(format!("Divided {} row(s) by their gcd.", reduced)));
+            self.update_deltas();
+        }
+
+        Ok(self)
+    }
 
-pub mod parser;
-pub mod steinitz;
-pub mod discrepancy;
-mod graph;
+    /// Removes rows that are equal to another row. If the rows
+    /// are equal but the entries of b differ there is no solution.
+    pub fn remove_duplicate_rows(mut self) -> Result<Self, ILPError> {
+        let rows:Vec<Vector> = (0..self.A.size.0).map(|i| self.A.row(i)).collect();
+        let mut keep = vec![true; rows.len()];
 
-pub type IntData = i32;
-pub type Cost = i32;
-pub type VarMapping = (String, usize);
+        for (i, row) in rows.iter().enumerate() {
+            if !keep[i] {
+                continue;
+            }
 
-#[derive(Hash, PartialEq, Eq, Clone)]
-pub struct Vector {
-    data: Vec<IntData>
-}
+            for (j, other) in rows.iter().enumerate().skip(i+1) {
+                if keep[j] && row == other {
+                    if self.b.data[i] != self.b.data[j] {
+                        log!(" -> Rows {} and {} are contradicting.", i+1, j+1);
+                        return Err(ILPError::NoSolution);
+                    }
 
-#[derive(Clone)]
-pub struct Matrix {
-    columns: Vec<Vector>,
-    size: (usize, usize) // rows, columns or (m,n)
-}
+                    keep[j] = false;
+                }
+            }
+        }
 
-#[allow(non_snake_case)]
-#[derive(Clone)]
-pub struct ILP {
-    pub A: Matrix,
-    pub b: Vector,
-    pub c: Vector,
-    delta_A: IntData,
-    delta_b: IntData,
-    named_variables: Vec<VarMapping>
-}
+        let removed = keep.iter().filter(|&&k| !k).count();
+        if removed == 0 {
+            return Ok(self);
+        }
 
-pub enum ILPError {
-    NoSolution,
-    Unbounded
-}
+        self.diagnose(Diagnostic::info(format!("Removed {} duplicate row(s).", removed)));
 
-impl ILP {
-    pub fn new(mat:Matrix, b:Vector, c:Vector) -> Self {
-        assert!(b.len() == mat.size.0);
-        assert!(c.len() == mat.size.1);
-        assert!(mat.size.0 > 0 && mat.size.1 > 0);
+        Ok(self.select_rows(&keep))
+    }
 
-        let da = mat.max_abs_entry();
-        let db = b.inf_norm();
+    /// Removes zero columns with non-positive cost, the corresponding
+    /// variables are 0 in an optimal solution. Zero columns with positive
+    /// cost are kept as they make a feasible ILP unbounded.
+    pub fn remove_zero_columns(mut self) -> Self {
+        let keep:Vec<bool> = self.A.iter()
+            .enumerate()
+            .map(|(j, column)| !column.is_zero() || self.cost_key(j) > (0, 0))
+            .collect();
 
-        assert!(da >= 0 && db >= 0);
-    
-        ILP {
-            A: mat,
-            b: b,
-            c: c,
-            delta_A: da,
-            delta_b: db,
-            named_variables: Vec::new()
+        let removed = keep.iter().filter(|&&k| !k).count();
+        if removed == 0 || removed == keep.len() {
+            return self;
         }
+
+        self.diagnose(Diagnostic::info(format!("Removed {} zero column(s).", removed)));
+
+        self.select_columns(&keep)
     }
 
-    pub fn with_named_vars(mat:Matrix, b:Vector, c:Vector, vars:Vec<VarMapping>) -> Self {
-        let mut ilp = ILP::new(mat, b, c);
+    /// Fixes a variable to the given value: the value times its column is
+    /// subtracted from b and the column is removed. The cost of the variable
+    /// is kept as a constant of the objective (see `objective_value`).
+    /// Returns `NoSolution` if a row becomes obviously
+    /// infeasible (all coefficients have the same sign but b has the opposite).
+    /// If it is the only column, it is replaced by a zero column without
+    /// cost instead (an ILP has at least one column) and b has to become 0.
+    pub fn fix_variable(mut self, var:&str, value:IntData) -> Result<Self, ILPError> {
+        let j = match self.named_variables.iter().position(|(name, _)| name == var) {
+            Some(pos) => self.named_variables.remove(pos).1,
+            None => return Err(ILPError::UnknownVariable(var.to_string()))
+        };
 
-        for (s, idx) in vars.iter() {
-            assert!(s.len() > 0);
-            assert!(idx < &ilp.c.len());
+        if value < 0 {
+            return Err(ILPError::NoSolution);
         }
 
-        let mut variables = vars;
-        variables.sort_by(|a,b| a.1.cmp(&b.1));
+        for (b, &a) in self.b.data.iter_mut().zip(self.A.columns[j].iter()) {
+            *b -= value * a;
+        }
 
-        ilp.named_variables = variables;
-        ilp
+        self.upper_bounds.retain(|(name, _)| name != var);
+        self.binaries.retain(|name| name != var);
+        self.fixed_variables.push((var.to_string(), value));
+        self.objective_offset += self.c.data[j] * value;
+        for column in self.variable_map.columns.iter_mut() {
+            if *column == Ok(j) {
+                *column = Err(value);
+            }
+        }
+
+        if self.A.size.1 == 1 {
+            if !self.b.is_zero() {
+                log!(" -> Ax=b can not be satisfied with {} = {}.", var, value);
+                return Err(ILPError::NoSolution);
+            }
+
+            self.A.columns[j] = Vector::zero(self.A.size.0);
+            self.c.data[j] = 0;
+            if let Some(c2) = self.c2.as_mut() {
+                c2.data[j] = 0;
+            }
+            self.delta_b = 0;
+            return Ok(self);
+        }
+
+        let mut keep = vec![true; self.A.size.1];
+        keep[j] = false;
+        let ilp = self.select_columns(&keep);
+
+        for (i, &b) in ilp.b.iter().enumerate() {
+            let row = ilp.A.row(i);
+
+            if (b > 0 && row.iter().all(|&a| a <= 0)) || (b < 0 && row.iter().all(|&a| a >= 0)) {
+                log!(" -> Row {} can not be satisfied with {} = {}.", i+1, var, value);
+                return Err(ILPError::NoSolution);
+            }
+        }
+
+        Ok(ilp)
     }
 
-    pub fn print_details(&self) {
-        println!("ILP details:");
-        println!(" -> constraints: {}", self.A.size.0);
-        println!(" -> variables: {:3}", self.A.size.1);
-        let list:Vec<&String> = self.named_variables.iter().map(|(s,_)| s).collect();
-        print!("    {:?}", list);
-        let slacks = self.A.size.1 - list.len();
-        if slacks > 0 {
-            println!(" + {} slack variables", slacks);
-        } else {
-            println!();
+    /// Appends a variable with the given column of A and cost. The cost is a
+    /// coefficient of the objective of the model (in its units and sense).
+    pub fn add_variable(mut self, name:&str, column:Vector, cost:IntData) -> Self {
+        assert_eq!(column.len(), self.A.size.0, "the column has to have an entry for every row");
+        assert!(!name.is_empty() && self.named_variables.iter().all(|(n, _)| n != name), "invalid variable name {}", name);
+
+        let j = self.A.size.1;
+        self.delta_A = self.delta_A.max(column.inf_norm());
+        self.A = self.A.hstack(column);
+        let sign = if self.sense == Sense::Maximize { 1 } else { -1 };
+        self.c.data.push(sign * cost * self.objective_scale);
+        if let Some(c2) = self.c2.as_mut() {
+            c2.data.push(0);
         }
-        println!(" -> \u{0394}    = {}", self.delta_A);
-        println!(" -> \u{2016}b\u{2016}\u{221E} = {}", self.delta_b);
-        if self.A.size.0 > 1 {
-            println!(" -> Matrix A:\n{}", self.A);
-        } else {
-            print!(  " -> Matrix A: {}", self.A);
+        self.named_variables.push((name.to_string(), j));
+        self.variable_map.columns.push(Ok(j));
+
+        self
+    }
+
+    /// Adds the constraint "objective <= cap" (in the units and sense of the
+    /// model) as a row with a slack variable.
+    pub fn add_objective_cap(mut self, cap:Cost) -> Self {
+        // objective = sign * (c*x + offset) / scale
+        let sign = if self.sense == Sense::Maximize { 1 } else { -1 };
+        let (m, n) = self.A.size;
+
+        for (column, &c) in self.A.columns.iter_mut().zip(self.c.iter()) {
+            column.data.push(sign * c);
+        }
+        self.A.size.0 += 1;
+        self.A = self.A.hstack(Vector::unit(m + 1, m));
+        self.b.data.push(cap * self.objective_scale - sign * self.objective_offset);
+        self.c.data.push(0);
+        if let Some(c2) = self.c2.as_mut() {
+            c2.data.push(0);
         }
-        println!(" -> b = {:?}", self.b);
-        println!(" -> c = {:?}\n", self.c);
+        self.slack_columns.push(Some(n));
+        self.variable_map.columns.push(Ok(n));
+        self.delta_A = self.A.max_abs_entry();
+        self.delta_b = self.b.inf_norm();
+
+        self
     }
 
-    pub fn print_solution(&self, x:&Vector) {
-        if self.named_variables.len() == 0 {
-            println!(" x={:?}", x);
-        } else {
-            for (name, idx) in self.named_variables.iter() {
-                println!(" {} = {}", name, x.data[*idx]);
+    /// Finds independent blocks of the ILP: the connected components of the
+    /// bipartite graph of rows and columns (with an edge if a_ij != 0).
+    /// Returns the rows and columns of every block. Zero rows and columns
+    /// are added to the first block.
+    pub fn blocks(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let (m, n) = self.A.size;
+
+        // union-find, rows are 0..m and columns m..m+n
+        let mut parent:Vec<usize> = (0..m+n).collect();
+        fn find(parent:&mut [usize], x:usize) -> usize {
+            let mut root = x;
+            while parent[root] != root {
+                root = parent[root];
             }
+            parent[x] = root;
+            root
         }
-    }
 
-    pub fn simplify(self) -> Self {
-        assert!(self.A.columns.len() > 1);
-        
-        let mut mat = Matrix {
-            columns: Vec::with_capacity(self.A.size.1 - 1),
-            size: (self.b.len(), 0)
-        };
-    
-        let mut c = Vector {
-            data: Vec::new()
-        };
-        
-        let mut var_names:Vec<Option<String>> = vec![None; self.A.size.1];
-        self.named_variables.iter().for_each(|(str, i)| var_names[*i] = Some(str.clone()));
-        
-        let mut skip = Vec::new();
-        for (i, col1) in self.A.iter().enumerate() {
-            if skip.contains(&i) {
+        for (j, column) in self.A.iter().enumerate() {
+            for (i, &a) in column.iter().enumerate() {
+                if a != 0 {
+                    let (r1, r2) = (find(&mut parent, i), find(&mut parent, m+j));
+                    parent[r1] = r2;
+                }
+            }
+        }
+
+        let mut blocks:Vec<(usize, Vec<usize>, Vec<usize>)> = Vec::new(); // (root, rows, columns)
+        let mut zero_rows = Vec::new();
+        let mut zero_columns = Vec::new();
+
+        for x in 0..m+n {
+            let root = find(&mut parent, x);
+
+            if root == x && parent.iter().filter(|&&p| p == x).count() == 1 {
+                // not connected to anything
+                if x < m { zero_rows.push(x) } else { zero_columns.push(x-m) }
                 continue;
             }
-    
-            let mut best = (col1, self.c.data[i]);
-            for (j, col2) in self.A.iter().enumerate().skip(i+1) {
-                if col1 == col2 {
-                    let cost = self.c.data[j];
-                    
-                    // keep column with highest cost/weight
-                    let removed = if cost > best.1 {
-                        best = (col2, cost);
-                        var_names.remove(i)
-                    } else {
-                        var_names.remove(j)
-                    };
 
-                    if let Some(name) = removed {
-                        println!("    {} = 0", name);
-                    }
+            let pos = match blocks.iter().position(|(r, _, _)| *r == root) {
+                Some(pos) => pos,
+                None => {
+                    blocks.push((root, Vec::new(), Vec::new()));
+                    blocks.len() - 1
+                }
+            };
+
+            if x < m { blocks[pos].1.push(x) } else { blocks[pos].2.push(x-m) }
+        }
+
+        if blocks.is_empty() {
+            return vec![(zero_rows, zero_columns)];
+        }
 
-                    skip.push(j);
+        let mut blocks:Vec<(Vec<usize>, Vec<usize>)> = blocks.into_iter()
+            .map(|(_, rows, columns)| (rows, columns))
+            .collect();
+        blocks.sort_by_key(|(_, columns)| columns[0]);
+
+        blocks[0].0.extend(zero_rows);
+        blocks[0].1.extend(zero_columns);
+        blocks[0].0.sort_unstable();
+        blocks[0].1.sort_unstable();
+
+        blocks
+    }
+
+    /// Splits the ILP into independent subproblems, see `ILP::blocks`.
+    pub fn decompose(&self) -> Vec<ILP> {
+        self.blocks().iter()
+            .map(|(rows, columns)| self.sub_ilp(rows, columns))
+            .collect()
+    }
+
+    /// Solves the independent blocks of the ILP separately and combines the solutions.
+    /// If a block has no solution neither has the ILP, even if another block
+    /// is unbounded (or stopped), so all blocks are solved before an error is
+    /// returned. Otherwise the error of the first failing block is returned.
+    pub fn solve_decomposed<F>(&self, mut solve:F) -> Result<Vector, ILPError>
+        where F: FnMut(&ILP) -> Result<Vector, ILPError>
+    {
+        let blocks = self.blocks();
+        log!(" -> Decomposed the ILP into {} block(s).", blocks.len());
+        let mut x = Vector::zero(self.A.size.1);
+        let mut error = None;
+
+        for (k, (rows, columns)) in blocks.iter().enumerate() {
+            log!();
+            log!("Block {}: {} constraint(s), {} variable(s)", k+1, rows.len(), columns.len());
+            match solve(&self.sub_ilp(rows, columns)) {
+                Ok(y) => {
+                    for (&j, &value) in columns.iter().zip(y.iter()) {
+                        x.data[j] = value;
+                    }
+                },
+                Err(ILPError::NoSolution) => return Err(ILPError::NoSolution),
+                Err(e) => if error.is_none() {
+                    error = Some(e);
                 }
             }
-            
-            mat.columns.push(best.0.clone());
-            mat.size.1 += 1;
-            c.data.push(best.1);
         }
 
-        let mappings = var_names.into_iter()
-            .enumerate()
-            .map(|(i, o)| match o {
-                Some(str) => Some((str, i)),
-                None      => None
+        match error {
+            Some(e) => Err(e),
+            None => Ok(x)
+        }
+    }
+
+    /// The ILP restricted to the given rows and columns.
+    fn sub_ilp(&self, rows:&[usize], columns:&[usize]) -> ILP {
+        let mat = Matrix {
+            columns: columns.iter()
+                .map(|&j| Vector { data: rows.iter().map(|&i| self.A.columns[j].data[i]).collect() })
+                .collect(),
+            size: (rows.len(), columns.len())
+        };
+        let b = Vector { data: rows.iter().map(|&i| self.b.data[i]).collect() };
+        let c = Vector { data: columns.iter().map(|&j| self.c.data[j]).collect() };
+        let c2 = self.c2.as_ref().map(|c2| Vector { data: columns.iter().map(|&j| c2.data[j]).collect() });
+
+        let mappings = self.named_variables.iter()
+            .filter_map(|(name, j)| columns.iter().position(|k| k == j).map(|idx| (name.clone(), idx)))
+            .collect();
+
+        let mut ilp = ILP::from_parts(mat, b, c, mappings);
+        ilp.c2 = c2;
+        ilp.upper_bounds = self.upper_bounds.iter()
+            .filter(|(name, _)| ilp.named_variables.iter().any(|(n, _)| n == name))
+            .cloned()
+            .collect();
+        ilp.binaries = self.binaries.iter()
+            .filter(|name| ilp.named_variables.iter().any(|(n, _)| n == *name))
+            .cloned()
+            .collect();
+        ilp.slack_columns = rows.iter()
+            .map(|&i| self.slack_columns[i].and_then(|j| columns.iter().position(|&k| k == j)))
+            .collect();
+        ilp.algorithm = self.algorithm.clone();
+        ilp.objective_scale = self.objective_scale;
+        ilp.sense = self.sense;
+        ilp
+    }
+
+    /// Keeps only the rows i with keep[i].
+    fn select_rows(self, keep:&[bool]) -> Self {
+        assert_eq!(keep.len(), self.b.len());
+
+        let select = |v:&Vector| Vector {
+            data: v.iter().zip(keep.iter()).filter(|(_, &k)| k).map(|(&x, _)| x).collect()
+        };
+
+        let columns:Vec<Vector> = self.A.iter().map(select).collect();
+        let rows = columns[0].len();
+        let mat = Matrix {
+            size: (rows, columns.len()),
+            columns
+        };
+
+        let mut ilp = ILP::from_parts(mat, select(&self.b), self.c, self.named_variables);
+        ilp.c2 = self.c2;
+        ilp.slack_columns = self.slack_columns.into_iter().zip(keep.iter()).filter(|(_, &k)| k).map(|(j, _)| j).collect();
+        ilp.upper_bounds = self.upper_bounds;
+        ilp.binaries = self.binaries;
+        ilp.fixed_variables = self.fixed_variables;
+        ilp.variable_map = self.variable_map;
+        ilp.diagnostics = self.diagnostics;
+        ilp.algorithm = self.algorithm;
+        ilp.objective_scale = self.objective_scale;
+        ilp.objective_offset = self.objective_offset;
+        ilp.sense = self.sense;
+        ilp
+    }
+
+    /// Keeps only the columns j with keep[j], the variables
+    /// of the removed columns are fixed to 0.
+    fn select_columns(self, keep:&[bool]) -> Self {
+        assert_eq!(keep.len(), self.c.len());
+
+        // new index of every kept column
+        let mut new_idx = vec![None; keep.len()];
+        let mut n = 0;
+        for (j, &k) in keep.iter().enumerate() {
+            if k {
+                new_idx[j] = Some(n);
+                n += 1;
+            }
+        }
+
+        let mat = Matrix {
+            columns: self.A.columns.into_iter().zip(keep.iter()).filter(|(_, &k)| k).map(|(v, _)| v).collect(),
+            size: (self.A.size.0, n)
+        };
+        let select = |v:Vector| Vector {
+            data: v.data.into_iter().zip(keep.iter()).filter(|(_, &k)| k).map(|(x, _)| x).collect()
+        };
+        let c = select(self.c);
+        let c2 = self.c2.map(select);
+
+        let mut fixed_variables = self.fixed_variables;
+        let mut variable_map = self.variable_map;
+        variable_map.select(&new_idx);
+        let mappings = self.named_variables.into_iter()
+            .filter_map(|(name, j)| match new_idx[j] {
+                Some(idx) => Some((name, idx)),
+                None => {
+                    log!("    {} = 0", name);
+                    fixed_variables.push((name, 0));
+                    None
+                }
             })
-            .flatten()
             .collect();
 
-        println!(" -> Removed {} column(s).", skip.len());
-    
-        ILP::with_named_vars(mat, self.b.clone(), c, mappings)
+        let mut ilp = ILP::from_parts(mat, self.b, c, mappings);
+        ilp.c2 = c2;
+        ilp.slack_columns = self.slack_columns.iter().map(|j| j.and_then(|j| new_idx[j])).collect();
+        ilp.upper_bounds = self.upper_bounds;
+        ilp.binaries = self.binaries;
+        ilp.fixed_variables = fixed_variables;
+        ilp.variable_map = variable_map;
+        ilp.diagnostics = self.diagnostics;
+        ilp.algorithm = self.algorithm;
+        ilp.objective_scale = self.objective_scale;
+        ilp.objective_offset = self.objective_offset;
+        ilp.sense = self.sense;
+        ilp
+    }
+}
+
+/// Structural equality: same `A`, `b`, `c`, `c2` and variable names,
+/// regardless of the order in which the names are stored.
+impl PartialEq for ILP {
+    fn eq(&self, other:&ILP) -> bool {
+        if self.A != other.A || self.b != other.b || self.c != other.c || self.c2 != other.c2 {
+            return false;
+        }
+
+        let mut names = self.named_variables.clone();
+        let mut other_names = other.named_variables.clone();
+        names.sort();
+        other_names.sort();
+
+        names == other_names
     }
 }
 
@@ -200,6 +1690,22 @@ impl Vector {
         self.data.len()
     }
 
+    /// Appends `fill` until the vector has length `new_len`.
+    pub fn pad(&self, new_len:usize, fill:IntData) -> Vector {
+        assert!(new_len >= self.len(), "padding can not shrink a vector");
+        let mut data = self.data.clone();
+        data.resize(new_len, fill);
+
+        Vector { data }
+    }
+
+    /// The first `new_len` entries.
+    pub fn truncate(&self, new_len:usize) -> Vector {
+        assert!(new_len <= self.len(), "truncating can not grow a vector");
+
+        Vector::from_slice(&self.data[..new_len])
+    }
+
     pub fn iter(&self) -> Iter<IntData> {
         self.data.iter()
     }
@@ -217,6 +1723,26 @@ impl Vector {
         }
     }
 
+    pub fn sub(&self, other:&Vector) -> Vector {
+        debug_assert!(self.len() == other.len());
+        let mut v = Vec::with_capacity(self.len());
+
+        for (x1,x2) in self.iter().zip(other.iter()) {
+            v.push(x1 - x2);
+        }
+
+        Vector {
+            data: v
+        }
+    }
+
+    /// Multiplies every entry by `factor`.
+    pub fn scale(&self, factor:IntData) -> Vector {
+        Vector {
+            data: self.iter().map(|&x| x * factor).collect()
+        }
+    }
+
     pub fn dot(&self, other: &Vector) -> IntData {
         debug_assert!(self.len() == other.len());
         let mut sum = 0;
@@ -238,12 +1764,13 @@ impl Vector {
         sum
     }
 
+    /// max |x_i| (0 for an empty vector)
     pub fn inf_norm(&self) -> IntData {
-        let mut max = self.data[0];
+        let mut max = 0;
 
-        for &x in self.iter().skip(1) {
-            if x > max {
-                max = x;
+        for &x in self.iter() {
+            if x.abs() > max {
+                max = x.abs();
             }
         }
 
@@ -260,14 +1787,18 @@ impl Vector {
         sum
     }
 
+    /// True if every entry is 0 (stops at the first non-zero entry).
     pub fn is_zero(&self) -> bool {
-        for &x in self.iter() {
-            if x != 0 {
-                return false;
-            }
-        }
+        self.iter().all(|&x| x == 0)
+    }
+
+    /// Number of non-zero entries.
+    pub fn nnz(&self) -> usize {
+        self.iter().filter(|&&x| x != 0).count()
+    }
 
-        true
+    pub fn non_negative(&self) -> bool {
+        self.iter().all(|&x| x >= 0)
     }
 
     pub fn as_f32_vec(&self) -> Vec<f32> {
@@ -280,16 +1811,43 @@ impl Vector {
         v
     }
 
-    pub fn max_distance(&self, v:&Vector, bound:IntData) -> bool {
-        debug_assert!(self.len() == v.len());
+    pub fn as_f64_vec(&self) -> Vec<f64> {
+        self.iter().map(|&x| x as f64).collect()
+    }
+
+    /// Returns k if self = k * other (for a non-zero other).
+    pub fn multiple_of(&self, other:&Vector) -> Option<IntData> {
+        let p = other.iter().position(|&x| x != 0)?;
+
+        if self.data[p] % other.data[p] != 0 {
+            return None;
+        }
+
+        let k = self.data[p] / other.data[p];
+        if self.iter().zip(other.iter()).all(|(&x, &y)| x == k * y) {
+            Some(k)
+        } else {
+            None
+        }
+    }
+
+    /// max_i |a_i - b_i|
+    pub fn linf_distance(&self, other:&Vector) -> IntData {
+        debug_assert!(self.len() == other.len());
+        let mut max = 0;
 
-        for (&a,&b) in self.iter().zip(v.iter()) {
-            if IntData::abs(a-b) > bound {
-                return false;
+        for (&a,&b) in self.iter().zip(other.iter()) {
+            let d = IntData::abs(a-b);
+            if d > max {
+                max = d;
             }
         }
 
-        true
+        max
+    }
+
+    pub fn max_distance(&self, v:&Vector, bound:IntData) -> bool {
+        self.linf_distance(v) <= bound
     }
 }
 
@@ -299,6 +1857,33 @@ impl fmt::Debug for Vector {
     }
 }
 
+impl SparseVector {
+    pub fn from_dense(v:&Vector) -> Self {
+        SparseVector {
+            size: v.len(),
+            entries: v.iter().enumerate().filter(|(_, &x)| x != 0).map(|(i, &x)| (i, x)).collect()
+        }
+    }
+
+    /// Adds self to v in place (only touches the non-zero entries).
+    pub fn add_to(&self, v:&mut Vector) {
+        debug_assert!(self.size == v.len());
+
+        for &(i, x) in self.entries.iter() {
+            v.data[i] += x;
+        }
+    }
+
+    /// Subtracts self from v in place, undoes `add_to`.
+    pub fn subtract_from(&self, v:&mut Vector) {
+        debug_assert!(self.size == v.len());
+
+        for &(i, x) in self.entries.iter() {
+            v.data[i] -= x;
+        }
+    }
+}
+
 impl Matrix {
     pub fn zero(m:usize, n:usize) -> Self {
         let mut columns = Vec::with_capacity(n);
@@ -330,6 +1915,64 @@ impl Matrix {
         }
     }
 
+    pub fn row(&self, i:usize) -> Vector {
+        Vector {
+            data: self.columns.iter().map(|column| column.data[i]).collect()
+        }
+    }
+
+    /// Fraction of non-zero entries.
+    pub fn density(&self) -> f64 {
+        let (m, n) = self.size;
+        let nnz:usize = self.iter().map(Vector::nnz).sum();
+
+        nnz as f64 / (m * n) as f64
+    }
+
+    /// Rank over the rationals, computed exactly with fraction-free
+    /// Gaussian elimination (Bareiss algorithm). None if the intermediate
+    /// values (minors of the matrix) do not fit into an i128.
+    pub fn rank(&self) -> Option<usize> {
+        let (m, n) = self.size;
+        let mut rows:Vec<Vec<i128>> = (0..m)
+            .map(|i| self.iter().map(|column| column.data[i] as i128).collect())
+            .collect();
+        let mut rank = 0;
+        let mut divisor = 1; // previous pivot
+
+        for j in 0..n {
+            if rank == m {
+                break;
+            }
+
+            let pivot = match (rank..m).find(|&i| rows[i][j] != 0) {
+                Some(i) => i,
+                None => continue
+            };
+            rows.swap(rank, pivot);
+
+            let pivot_row = rows[rank].clone();
+            for row in rows.iter_mut().skip(rank + 1) {
+                let factor = row[j];
+
+                // the division is exact (the entries are minors of the matrix)
+                for (x, &p) in row.iter_mut().zip(pivot_row.iter()).skip(j + 1) {
+                    *x = x.checked_mul(pivot_row[j])?.checked_sub(factor.checked_mul(p)?)? / divisor;
+                }
+                row[j] = 0;
+            }
+
+            divisor = pivot_row[j];
+            rank += 1;
+        }
+
+        Some(rank)
+    }
+
+    pub fn sparse_columns(&self) -> Vec<SparseVector> {
+        self.iter().map(SparseVector::from_dense).collect()
+    }
+
     pub fn num_cols(&self) -> usize {
         self.columns.len()
     }
@@ -354,6 +1997,25 @@ impl Matrix {
         false
     }
 
+    /// Pairs (i, j) with i < j of non-zero columns with A_i = -A_j.
+    pub fn opposite_column_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+
+        for (i, v) in self.iter().enumerate() {
+            if v.is_zero() {
+                continue;
+            }
+
+            for (j, w) in self.iter().enumerate().skip(i+1) {
+                if v.iter().zip(w.iter()).all(|(&a, &b)| a == -b) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+
+        pairs
+    }
+
     pub fn has_zero_columns(&self) -> bool {
         for v in self.iter() {
             if v.is_zero() {
@@ -382,18 +2044,20 @@ impl Matrix {
         )
     }
 
+    /// Appends a column (with an entry for every row).
+    pub fn hstack(mut self, column:Vector) -> Matrix {
+        assert_eq!(column.len(), self.size.0);
+        self.columns.push(column);
+        self.size.1 += 1;
+        self
+    }
+
     pub fn add_to_entry(&mut self, i:usize, j:usize, val:IntData) {
         self.columns[j].data[i] += val;
     }
 
     pub fn non_negative(&self) -> bool {
-        for c in self.columns.iter() {
-            if c.iter().filter(|&&x| x < 0).count() > 0 {
-                return false;
-            }
-        }
-
-        true
+        self.iter().all(Vector::non_negative)
     }
 }
 
@@ -413,3 +2077,53 @@ impl Display for Matrix {
         write!(f, "{}", str)
     }
 }
+
+/// Columns of a matrix with i128 entries (for the Hermite normal form).
+type WideColumns = Vec<Vec<i128>>;
+
+/// columns[j] -= q * columns[r] (j != r), `Overflow` if an entry exceeds the i128 range.
+fn subtract_column(columns:&mut [Vec<i128>], j:usize, r:usize, q:i128) -> Result<(), ILPError> {
+    debug_assert!(j != r);
+    let (target, source) = if j < r {
+        let (left, right) = columns.split_at_mut(r);
+        (&mut left[j], &right[0])
+    } else {
+        let (left, right) = columns.split_at_mut(j);
+        (&mut right[0], &left[r])
+    };
+
+    for (x, &y) in target.iter_mut().zip(source.iter()) {
+        *x = q.checked_mul(y).and_then(|qy| x.checked_sub(qy)).ok_or(ILPError::Overflow)?;
+    }
+
+    Ok(())
+}
+
+/// The dot product of `row` and `y`, or None on overflow.
+fn checked_dot(row:impl Iterator<Item=i128>, y:&[i128]) -> Option<i128> {
+    row.zip(y.iter()).try_fold(0i128, |sum, (a, &y)| sum.checked_add(a.checked_mul(y)?))
+}
+
+/// a / b rounded to the nearest integer (b != 0), the remainder
+/// a - q*b is at most |b|/2 in absolute value.
+fn round_div(a:i128, b:i128) -> i128 {
+    let q = a / b;
+    let r = a - q * b;
+    if 2 * r.abs() > b.abs() {
+        q + r.signum() * b.signum()
+    } else {
+        q
+    }
+}
+
+fn gcd(a:IntData, b:IntData) -> IntData {
+    let (mut a, mut b) = (a.abs(), b.abs());
+
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+
+    a
+}
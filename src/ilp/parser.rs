@@ -1,7 +1,9 @@
 use pest::Parser;
 use pest::iterators::Pair;
+use std::fmt;
 use std::fs;
-use super::{ILP, Vector, Matrix};
+use std::io;
+use super::{ILP, ILPError, Vector, Matrix, Sense, Diagnostic};
 
 type Map<K,V> = hashbrown::HashMap<K,V>;
 type Set<T> = hashbrown::HashSet<T>;
@@ -12,40 +14,138 @@ pub struct ILPFileParser;
 
 struct Multiple(i32,String);
 struct Sum(i32,Vec<Multiple>);
+/// `lower <= variable <= upper`
+struct Bound(i32,String,i32);
 enum Constraint {
     Equation   { left: Sum, right: Sum },
     Inequality { left: Sum, right: Sum, leq:bool }
 }
 
-pub fn parse_file(file:&str) -> Result<ILP, ()> {
-    println!("Reading file {}...", file);
-    let unparsed_file = fs::read_to_string(file).expect("cannot read file");
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    Syntax(pest::error::Error<Rule>),
+    /// Syntax that is recognized but not supported, e.g. `1e3` (coefficients
+    /// have to be written as plain integers).
+    Unsupported { line: usize, token: String },
+    /// The ILP has no constraints or no variables (or is invalid otherwise, see `ILP::new`).
+    Degenerate(&'static str),
+    /// A constraint without variables that is false, e.g. `3 = 5`.
+    Infeasible { line: usize, constraint: String }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "Cannot read file: {}", e),
+            ParseError::Syntax(e) => write!(f, "Syntax error:\n{}", e),
+            ParseError::Unsupported { line, token } => write!(
+                f, "Unsupported coefficient '{}' in line {}, only integers are supported.", token, line
+            ),
+            ParseError::Degenerate(reason) => write!(f, "Invalid ILP: {}.", reason),
+            ParseError::Infeasible { line, constraint } => write!(
+                f, "The constraint '{}' in line {} is false, the ILP has no solution.", constraint, line
+            )
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::Syntax(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+pub fn parse_file(file:&str) -> Result<ILP, ParseError> {
+    log!("Reading file {}...", file);
+    let unparsed_file = read_input(file).map_err(ParseError::Io)?;
 
-    // parse file
-    println!("Parsing file...");
-    let file = ILPFileParser::parse(Rule::ilp, &unparsed_file)
-                .expect("unsuccessful parse")
+    parse_str(&unparsed_file)
+}
+
+/// Reads a file, files ending with `.gz` are decompressed
+/// (requires the `gzip` feature).
+fn read_input(file:&str) -> io::Result<String> {
+    if !file.ends_with(".gz") {
+        return fs::read_to_string(file);
+    }
+
+    #[cfg(feature = "gzip")]
+    {
+        use io::Read;
+        let mut content = String::new();
+        flate2::read::GzDecoder::new(fs::File::open(file)?).read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    Err(io::Error::other("gzipped files require the gzip feature"))
+}
+
+pub fn parse_str(unparsed_file:&str) -> Result<ILP, ParseError> {
+    log!("Parsing file...");
+    let file = ILPFileParser::parse(Rule::ilp, unparsed_file)
+                .map_err(ParseError::Syntax)?
                 .next().unwrap();
+
+    // reject scientific notation with a clear error
+    if let Some(pair) = file.clone().into_inner().flatten().find(|p| p.as_rule() == Rule::scientific) {
+        return Err(ParseError::Unsupported {
+            line: pair.as_span().start_pos().line_col().0,
+            token: pair.as_str().to_string()
+        });
+    }
     
     let mut variables = Map::<String, usize>::new();
     let maximize;
     let objective_tree;
+    let mut secondary_tree = None;
     let constraints_tree;
+    let mut binaries = Vec::new();
+    let mut bounds = Vec::new();
+    let mut algorithm = None;
 
     {
         let mut iterator = file.into_inner();
-        maximize = iterator.next().unwrap().as_str().to_lowercase() == "maximize";
+        maximize = is_maximize(iterator.next().unwrap());
         objective_tree = iterator.next().unwrap();
-        constraints_tree = iterator.next().unwrap();
+        if iterator.peek().map(|pair| pair.as_rule()) == Some(Rule::secondary) {
+            let mut inner = iterator.next().unwrap().into_inner();
+            let maximize = is_maximize(inner.next().unwrap());
+            secondary_tree = Some((maximize, inner.next().unwrap()));
+        }
+        constraints_tree = match iterator.peek() {
+            Some(pair) if pair.as_rule() == Rule::constraints => iterator.next().unwrap(),
+            _ => return Err(ParseError::Degenerate("there are no constraints"))
+        };
+
+        for pair in iterator {
+            match pair.as_rule() {
+                Rule::binary => {
+                    for var in pair.into_inner() {
+                        binaries.push(var.as_str().to_string());
+                    }
+                },
+                Rule::bounds => bounds.extend(pair.into_inner().map(bound)),
+                Rule::solver => algorithm = pair.into_inner().next().map(|p| p.as_str().to_string()),
+                _ => {}
+            }
+        }
     }
 
     // find variables
     {
         let vars1 = find_variables(&objective_tree);
         let vars2 = find_variables(&constraints_tree);
+        let vars3:Vec<String> = bounds.iter().map(|b:&Bound| b.1.clone()).collect();
+        let vars4 = secondary_tree.as_ref().map_or(Vec::new(), |(_, tree)| find_variables(tree));
         let mut list = Vec::new();
         
-        for var in vars1.iter().chain(vars2.iter()) {
+        for var in vars1.iter().chain(vars2.iter()).chain(binaries.iter()).chain(vars3.iter()).chain(vars4.iter()) {
             if !variables.contains_key(var) {
                 list.push(var);
                 variables.insert(var.clone(), variables.len());
@@ -53,34 +153,52 @@ pub fn parse_file(file:&str) -> Result<ILP, ()> {
         }
     }
 
+    check_constant_constraints(&constraints_tree)?;
     let constraints = get_constraints(constraints_tree);
-    let inequalities = constraints.iter().filter(|c| matches!(c, Constraint::Inequality{..})).count();
-    let m = constraints.len();
-    let n = variables.len() + inequalities; // a slack var for every inequality
+
+    if constraints.is_empty() && binaries.is_empty() && bounds.is_empty() {
+        return Err(ParseError::Degenerate("there are no constraints with variables"));
+    }
+    let inequalities = constraints.iter().filter(|(_, _, c)| matches!(c, Constraint::Inequality{..})).count();
+    // binary variables are encoded as x + s = 1, bounds as x + s = u and x - s = l
+    let bound_rows = binaries.len() + bounds.iter().map(|b| if b.0 > 0 { 2 } else { 1 }).sum::<usize>();
+    let m = constraints.len() + bound_rows;
+    let n = variables.len() + inequalities + bound_rows; // a slack var for every inequality
+    if n == 0 {
+        return Err(ParseError::Degenerate("there are no variables"));
+    }
+
     let mut a = Matrix::zero(m, n);
     let mut b = Vector::zero(m);
     let mut c = Vector::zero(n);
 
-    // objective -> c Vector
+    // objective -> c Vector (as written, the sense is applied below)
     for m in multiple_sum(objective_tree).1 {
         let i = *variables.get(&m.1).unwrap();
-        if maximize {
-            c.data[i] += m.0;
-        } else {
-            c.data[i] -= m.0;
-        }
-        
+        c.data[i] += m.0;
     }
 
+    // secondary objective -> c2 Vector (always maximized)
+    let c2 = secondary_tree.map(|(maximize, tree)| {
+        let mut c2 = Vector::zero(n);
+        for m in multiple_sum(tree).1 {
+            let i = *variables.get(&m.1).unwrap();
+            c2.data[i] += if maximize { m.0 } else { -m.0 };
+        }
+        c2
+    });
+
     // constraints -> A matrix
     let mut slack = 0;
-    for (row, c) in constraints.iter().enumerate() {
+    let mut slack_columns = vec![None; m];
+    for (row, (_, _, c)) in constraints.iter().enumerate() {
         let (left, right) = match c {
             Constraint::Equation{ left, right } => (left, right),
             Constraint::Inequality{ left, right, leq } => {
                 let j = variables.len() + slack;
                 slack += 1;
                 a.add_to_entry(row, j, if *leq {1} else {-1});
+                slack_columns[row] = Some(j);
                 (left,  right)
             }
         };
@@ -96,9 +214,94 @@ pub fn parse_file(file:&str) -> Result<ILP, ()> {
         }
     }
 
-    println!();
+    // binary variables: x + s = 1, bounds: x + s = u and x - s = l (if l > 0)
+    let upper = binaries.iter().map(|var| (var, 1)).chain(bounds.iter().map(|b| (&b.1, b.2)));
+    let lower = bounds.iter().filter(|b| b.0 > 0).map(|b| (&b.1, b.0));
+    for (k, ((var, value), sign)) in upper.map(|u| (u, 1)).chain(lower.map(|l| (l, -1))).enumerate() {
+        let row = constraints.len() + k;
+        let j = *variables.get(var).unwrap();
+        a.add_to_entry(row, j, 1);
+        a.add_to_entry(row, variables.len() + slack, sign);
+        b.data[row] = value;
+        slack_columns[row] = Some(variables.len() + slack);
+        slack += 1;
+    }
 
-    Ok(ILP::with_named_vars(a,b,c,variables.drain().collect()))
+    // every inequality and bound row has its own slack column, in order
+    assert_eq!(slack, inequalities + bound_rows, "slack count does not match the inequality count");
+    assert!(
+        slack_columns.iter().flatten().enumerate().all(|(k, &j)| j == variables.len() + k),
+        "a slack column is used by two constraints"
+    );
+
+    // variables that only appear with coefficient 0 (e.g. `0*x`) are kept, they
+    // are removed by preprocessing (or the ILP is unbounded if their cost is positive)
+    let mut zero_columns:Vec<(&String, usize)> = variables.iter()
+        .filter(|(_, &j)| a.columns[j].is_zero())
+        .map(|(name, &j)| (name, j))
+        .collect();
+    zero_columns.sort_by_key(|&(_, j)| j);
+    let mut diagnostics = Vec::new();
+    for (name, _) in zero_columns {
+        let diagnostic = Diagnostic::warning(format!("{} only has zero coefficients in the constraints.", name));
+        diagnostic.log();
+        diagnostics.push(diagnostic);
+    }
+
+    // equations whose variables cancel, e.g. `x1 + 2 = x1 + 2`, are
+    // dropped if they are satisfied and make the ILP infeasible otherwise
+    let mut keep = vec![true; m];
+    for (row, (line, text, _)) in constraints.iter().enumerate() {
+        if a.row(row).is_zero() {
+            if b.data[row] != 0 {
+                return Err(ParseError::Infeasible { line: *line, constraint: text.clone() });
+            }
+
+            let diagnostic = Diagnostic::info(format!("Dropped the constraint '{}' in line {}, its variables cancel.", text, line));
+            diagnostic.log();
+            diagnostics.push(diagnostic);
+            keep[row] = false;
+        }
+    }
+    if keep.iter().all(|&k| !k) {
+        return Err(ParseError::Degenerate("the variables of all constraints cancel"));
+    }
+
+    log!();
+
+    let mut ilp = ILP::with_named_vars(a,b,c,variables.drain().collect()).map_err(|e| match e {
+        ILPError::InvalidModel(reason) => ParseError::Degenerate(reason),
+        _ => ParseError::Degenerate("the ILP is invalid")
+    })?;
+    if !maximize {
+        ilp.set_sense(Sense::Minimize);
+    }
+    ilp.c2 = c2;
+    ilp.slack_columns = slack_columns;
+    ilp.algorithm = algorithm;
+    ilp.diagnostics = diagnostics;
+    ilp.upper_bounds = binaries.iter().map(|var| (var.clone(), 1))
+        .chain(bounds.into_iter().map(|b| (b.1, b.2)))
+        .collect();
+    ilp.binaries = binaries;
+
+    if keep.contains(&false) {
+        ilp = ilp.select_rows(&keep);
+    }
+
+    Ok(ilp)
+}
+
+/// The objective sense of a `mode` (the grammar only accepts
+/// `maximize` and `minimize`, in any case).
+fn is_maximize(pair: Pair<Rule>) -> bool {
+    assert_eq!(pair.as_rule(), Rule::mode);
+
+    match pair.as_str().to_lowercase().as_str() {
+        "maximize" => true,
+        "minimize" => false,
+        mode => unreachable!("unknown objective sense {}", mode)
+    }
 }
 
 fn find_variables(tree: &Pair<Rule>) -> Vec<String> {
@@ -159,29 +362,93 @@ fn multiple(pair: Pair<Rule>) -> Multiple {
     Multiple(multiple, var_name)
 }
 
-fn constraint(pair: Pair<Rule>) -> Constraint {
+fn bound(pair: Pair<Rule>) -> Bound {
+    assert_eq!(pair.as_rule(), Rule::bound);
+
+    let mut lower = 0;
+    let mut var_name = "".to_string();
+    let mut upper = 0;
+
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::integer if var_name.is_empty() => lower = p.as_str().parse().unwrap(),
+            Rule::integer  => upper = p.as_str().parse().unwrap(),
+            Rule::variable => var_name = p.as_str().to_string(),
+            _ => unreachable!()
+        }
+    }
+
+    Bound(lower, var_name, upper)
+}
+
+/// The constraints of a line, a range `l <= sum <= u` becomes `l <= sum` and `sum <= u`.
+fn constraint(pair: Pair<Rule>) -> Vec<Constraint> {
     let rule = pair.as_rule();
     let mut iter = pair.into_inner();
     let left  = multiple_sum(iter.next().unwrap());
     let right = multiple_sum(iter.next().unwrap());
 
     match rule {
-        Rule::equation => Constraint::Equation { left: left, right: right },
-        Rule::leq      => Constraint::Inequality { left: left, right: right, leq: true },
-        Rule::geq      => Constraint::Inequality { left: left, right: right, leq: false },
+        Rule::equation => vec![Constraint::Equation { left: left, right: right }],
+        Rule::leq      => vec![Constraint::Inequality { left: left, right: right, leq: true }],
+        Rule::geq      => vec![Constraint::Inequality { left: left, right: right, leq: false }],
+        Rule::range    => {
+            let middle = Sum(right.0, right.1.iter().map(|m| Multiple(m.0, m.1.clone())).collect());
+            let upper  = multiple_sum(iter.next().unwrap());
+
+            vec![
+                Constraint::Inequality { left, right, leq: true },
+                Constraint::Inequality { left: middle, right: upper, leq: true }
+            ]
+        },
         _              => unreachable!()
     }
 }
 
-fn get_constraints(pair: Pair<Rule>) -> Vec<Constraint> {
+/// Constraints without variables (e.g. `3 = 3`) are either always true
+/// or false. Returns an error for the first false one.
+fn check_constant_constraints(pair: &Pair<Rule>) -> Result<(), ParseError> {
+    for p in pair.clone().into_inner().flatten() {
+        if !matches!(p.as_rule(), Rule::equation | Rule::leq | Rule::geq | Rule::range) || has_variables(&p) {
+            continue;
+        }
+
+        let line = p.as_span().start_pos().line_col().0;
+        let text = p.as_str().trim().to_string();
+
+        let satisfied = constraint(p).iter().all(|c| match c {
+            Constraint::Equation { left, right } => left.0 == right.0,
+            Constraint::Inequality { left, right, leq: true }  => left.0 <= right.0,
+            Constraint::Inequality { left, right, leq: false } => left.0 >= right.0
+        });
+
+        if !satisfied {
+            return Err(ParseError::Infeasible { line, constraint: text });
+        }
+    }
+
+    Ok(())
+}
+
+fn has_variables(pair: &Pair<Rule>) -> bool {
+    pair.clone().into_inner().flatten().any(|p| p.as_rule() == Rule::variable)
+}
+
+/// Returns the constraints with variables together with their line and text,
+/// constant constraints are dropped (see `check_constant_constraints`).
+fn get_constraints(pair: Pair<Rule>) -> Vec<(usize, String, Constraint)> {
     assert_eq!(pair.as_rule(), Rule::constraints);
 
-    fn f(v:&mut Vec<Constraint>, pair:Pair<Rule>) {
+    fn f(v:&mut Vec<(usize, String, Constraint)>, pair:Pair<Rule>) {
         for p in pair.into_inner() {
             match p.as_rule() {
-                Rule::equation    => v.push(constraint(p)),
-                Rule::leq         => v.push(constraint(p)),
-                Rule::geq         => v.push(constraint(p)),
+                Rule::equation | Rule::leq | Rule::geq | Rule::range => {
+                    if has_variables(&p) {
+                        let line = p.as_span().start_pos().line_col().0;
+                        let text = p.as_str().trim().to_string();
+                        v.extend(constraint(p).into_iter().map(|c| (line, text.clone(), c)));
+                    }
+                },
                 Rule::constraints => f(v, p),
                 _                 => unreachable!()
             }
@@ -0,0 +1,59 @@
+use super::{ILP, Vector, ILPError, IntData, Cost};
+
+/*
+    Exhaustive search over all x in [0, var_upper]^n.
+    Only feasible for tiny instances, used as a ground truth for the other algorithms.
+*/
+
+pub fn brute_force_solve(ilp:&ILP, var_upper:IntData) -> Result<Vector, ILPError> {
+    assert!(var_upper >= 0);
+
+    let n = ilp.A.size.1;
+    let mut x = Vector::zero(n);
+    let mut best:Option<(Vector, Cost)> = None;
+
+    loop {
+        if is_solution(ilp, &x) {
+            let cost = x.dot(&ilp.c);
+            let better = match best {
+                Some((_, best_cost)) => cost > best_cost,
+                None => true
+            };
+
+            if better {
+                best = Some((x.clone(), cost));
+            }
+        }
+
+        // next x (odometer order)
+        let mut i = 0;
+        while i < n && x.data[i] == var_upper {
+            x.data[i] = 0;
+            i += 1;
+        }
+
+        if i == n {
+            break;
+        }
+
+        x.data[i] += 1;
+    }
+
+    match best {
+        Some((x, _)) => Ok(x),
+        None => Err(ILPError::NoSolution)
+    }
+}
+
+/// Ax = b
+fn is_solution(ilp:&ILP, x:&Vector) -> bool {
+    let mut ax = Vector::zero(ilp.b.len());
+
+    for (column, &k) in ilp.A.iter().zip(x.iter()) {
+        for (i, &a) in column.iter().enumerate() {
+            ax.data[i] += a * k;
+        }
+    }
+
+    ax == ilp.b
+}
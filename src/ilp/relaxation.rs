@@ -0,0 +1,172 @@
+use super::{ILP, ILPError};
+
+/*
+    LP relaxation: max c*x s.t. Ax = b, x >= 0 (x real)
+    solved with a dense two-phase simplex (Bland's rule, no cycling).
+    Only meant for small instances (bounds, heuristics, sensitivity).
+*/
+
+const EPS: f64 = 1e-9;
+
+/// Rows of the simplex tableau, the last entry of a row is its right-hand side.
+type Tableau = Vec<Vec<f64>>;
+
+/// Optimal solution of the LP relaxation.
+pub struct LPSolution {
+    pub x: Vec<f64>,
+    pub objective: f64,
+    /// Dual values y of the constraints, at the optimum A^T y >= c
+    /// and c*x = b*y hold.
+    pub duals: Vec<f64>
+}
+
+pub fn solve(ilp:&ILP) -> Result<LPSolution, ILPError> {
+    let (m, n) = ilp.A.size;
+    let (mut tableau, mut basis, signs) = phase1(ilp)?;
+
+    // phase 2: the actual objective, artificial variables can not enter the basis
+    let cost:Vec<f64> = (0..n + m).map(|j| if j < n { ilp.c.data[j] as f64 } else { 0.0 }).collect();
+
+    simplex(&mut tableau, &mut basis, &cost, n)?;
+
+    let mut x = vec![0.0; n];
+    for (k, &j) in basis.iter().enumerate() {
+        if j < n {
+            x[j] = tableau[k][n + m];
+        }
+    }
+
+    // y^T = c_B^T B^-1, the inverse of the basis is in the artificial columns
+    let duals = (0..m).map(|i| {
+        let y:f64 = basis.iter().enumerate().map(|(k, &j)| cost[j] * tableau[k][n + i]).sum();
+        signs[i] * y
+    }).collect();
+
+    Ok(LPSolution {
+        objective: objective(&tableau, &basis, &cost),
+        x,
+        duals
+    })
+}
+
+/// True if Ax = b has a real solution x >= 0, i.e. b is in the cone
+/// spanned by the columns of A (otherwise the ILP has no solution either).
+pub fn is_feasible(ilp:&ILP) -> bool {
+    phase1(ilp).is_ok()
+}
+
+/// Finds a feasible basis, returns the tableau, the basis and the signs
+/// the rows were multiplied with (or `NoSolution`).
+fn phase1(ilp:&ILP) -> Result<(Tableau, Vec<usize>, Vec<f64>), ILPError> {
+    let (m, n) = ilp.A.size;
+
+    // make b >= 0 by negating rows, the artificial variables form the initial basis
+    let signs:Vec<f64> = ilp.b.iter().map(|&b| if b < 0 { -1.0 } else { 1.0 }).collect();
+    let mut tableau:Tableau = (0..m).map(|i| {
+        let mut row = vec![0.0; n + m + 1];
+
+        for (j, column) in ilp.A.iter().enumerate() {
+            row[j] = signs[i] * column.data[i] as f64;
+        }
+
+        row[n + i] = 1.0;
+        row[n + m] = signs[i] * ilp.b.data[i] as f64;
+        row
+    }).collect();
+    let mut basis:Vec<usize> = (n..n+m).collect();
+
+    // phase 1: minimize the sum of the artificial variables
+    let mut cost = vec![0.0; n + m];
+    for c in cost.iter_mut().skip(n) {
+        *c = -1.0;
+    }
+
+    let bounded = simplex(&mut tableau, &mut basis, &cost, n + m).is_ok();
+    debug_assert!(bounded, "phase 1 is bounded by 0");
+
+    if objective(&tableau, &basis, &cost) < -EPS {
+        return Err(ILPError::NoSolution);
+    }
+
+    // drive the artificial variables out of the basis (if possible)
+    for k in 0..m {
+        if basis[k] >= n {
+            if let Some(j) = (0..n).find(|&j| tableau[k][j].abs() > EPS) {
+                pivot(&mut tableau, &mut basis, k, j);
+            }
+            // otherwise the row is redundant and the artificial variable stays 0
+        }
+    }
+
+    Ok((tableau, basis, signs))
+}
+
+/// Dual values of the constraints at the optimum of the LP relaxation.
+pub fn dual_values(ilp:&ILP) -> Result<Vec<f32>, ILPError> {
+    solve(ilp).map(|lp| lp.duals.iter().map(|&y| y as f32).collect())
+}
+
+/// Maximizes cost*x where only the first `entering` columns may enter the basis.
+fn simplex(tableau:&mut [Vec<f64>], basis:&mut [usize], cost:&[f64], entering:usize) -> Result<(), ILPError> {
+    let rhs = tableau.first().map_or(0, |row| row.len() - 1);
+
+    loop {
+        // Bland's rule: the first column with positive reduced cost enters
+        let column = (0..entering).find(|&j| {
+            let reduced:f64 = cost[j] - basis.iter().enumerate()
+                .map(|(k, &b)| cost[b] * tableau[k][j])
+                .sum::<f64>();
+
+            reduced > EPS
+        });
+
+        let j = match column {
+            Some(j) => j,
+            None => return Ok(())
+        };
+
+        // ratio test (ties: smallest basis index)
+        let mut leaving:Option<(usize, f64)> = None;
+        for (k, row) in tableau.iter().enumerate() {
+            if row[j] > EPS {
+                let ratio = row[rhs] / row[j];
+
+                leaving = match leaving {
+                    Some((l, r)) if r < ratio - EPS || (ratio - r).abs() <= EPS && basis[l] < basis[k] => Some((l, r)),
+                    _ => Some((k, ratio))
+                };
+            }
+        }
+
+        match leaving {
+            Some((k, _)) => pivot(tableau, basis, k, j),
+            None => return Err(ILPError::Unbounded)
+        }
+    }
+}
+
+fn pivot(tableau:&mut [Vec<f64>], basis:&mut [usize], k:usize, j:usize) {
+    let p = tableau[k][j];
+    for x in tableau[k].iter_mut() {
+        *x /= p;
+    }
+
+    let pivot_row = tableau[k].clone();
+    for (l, row) in tableau.iter_mut().enumerate() {
+        if l != k && row[j] != 0.0 {
+            let factor = row[j];
+
+            for (x, &y) in row.iter_mut().zip(pivot_row.iter()) {
+                *x -= factor * y;
+            }
+        }
+    }
+
+    basis[k] = j;
+}
+
+fn objective(tableau:&[Vec<f64>], basis:&[usize], cost:&[f64]) -> f64 {
+    basis.iter().zip(tableau.iter())
+        .map(|(&j, row)| cost[j] * row[row.len() - 1])
+        .sum()
+}
@@ -1,31 +1,291 @@
 use num_traits::Float;
-use super::{ILP, Vector, ILPError, Cost};
+use super::{ILP, Vector, ILPError, Cost, IntData, SolverOptions, ProgressEvent, TieBreak, SolveResult, SPARSE_DENSITY};
 use std::time::Instant;
 use super::graph::*;
-use std::io;
-use std::io::Write;
-use ignore_result::Ignore;
+use super::heuristics;
 
-/* 
+/*
     based on https://arxiv.org/abs/1707.00481v3
 */
 
-pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
-    println!("Solving ILP with the Eisenbrand & Weismantel algorithm...");
+/// A path from the origin to b: the used columns in order together with
+/// the cost of the path up to (and including) that column.
+pub type SolutionPath = Vec<(usize, Cost)>;
+
+/// Largest number of nodes printed by `SolverOptions::dump_costs`.
+const MAX_DUMP_NODES: usize = 1000;
+
+/// The estimated graph memory is reported once it exceeds this many bytes
+/// (and again whenever it has doubled).
+const MEMORY_REPORT_START: usize = 64 << 20;
+
+/// `count_region_points` enumerates bounding boxes up to this size.
+const MAX_REGION_ENUMERATION: f64 = 1e7;
+
+/// Statistics of the graph construction.
+pub struct SolveStats {
+    pub vertices: usize,
+    pub edges: usize,
+    pub depth: usize,
+    /// Number of new vertices (the surface) of every depth.
+    pub surface_sizes: Vec<usize>,
+    pub max_surface_size: usize,
+    /// Number of points x + A_i that were examined and that were within the search region.
+    pub candidates: usize,
+    pub accepted: usize,
+    /// Accepted candidates that were already in the graph (only an edge was added).
+    pub merged: usize,
+    /// Vertices in the outermost layer of the final search region, see
+    /// `count_boundary_nodes` (0 if the construction stopped at a limit).
+    pub boundary_vertices: usize
+}
+
+pub fn solve(ilp:&ILP, options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> Result<Vector, ILPError> {
+    solve_with_path(ilp, options, progress).map(|(x, _)| x)
+}
+
+/// Like `solve` but additionally returns the optimal path in the graph.
+pub fn solve_with_path(ilp:&ILP, options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> Result<(Vector, SolutionPath), ILPError> {
+    solve_internal(ilp, options, progress).0.map(|(x, path, _)| (x, path))
+}
+
+/// Like `solve` but additionally returns statistics of the graph construction.
+pub fn solve_with_stats(ilp:&ILP, options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> (Result<Vector, ILPError>, SolveStats) {
+    let (result, stats) = solve_internal(ilp, options, progress);
+
+    (result.map(|(x, _, _)| x), stats)
+}
+
+/// Like `solve` but distinguishes optimal solutions from solutions that are
+/// not proven to be optimal (time limit, iteration limit, bound factor < 1).
+pub fn solve2(ilp:&ILP, options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> SolveResult {
+    let result = solve_internal(ilp, options, progress).0
+        .map(|(x, _, optimal)| (x, optimal && options.bound_factor >= 1.0));
+
+    SolveResult::new(ilp, result)
+}
+
+/// The result also tells whether the solution is proven to be optimal.
+fn solve_internal(ilp:&ILP, options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> (Result<(Vector, SolutionPath, bool), ILPError>, SolveStats) {
+    log!("Solving ILP with the Eisenbrand & Weismantel algorithm...");
     let start = Instant::now();
 
+    let (mut graph, stats, stopped) = construct_graph(ilp, &[&ilp.b], options, start, progress);
+    #[cfg(debug_assertions)]
+    validate_graph(ilp, &graph);
+    let result = find_solution(ilp, &mut graph, options, stopped, start, progress);
+
+    if let Err(ILPError::NoSolution) = result {
+        let bound = options.bound_factor * radius_at_depth(ilp, stats.depth as i32);
+        explain_no_solution(ilp, &graph, &ilp.b, bound);
+    }
+
+    (result, stats)
+}
+
+/// Finds the longest path to b in the constructed graph. `stopped` is the
+/// reason why the construction stopped early (if it did). The path is
+/// optimal (true) unless a time or iteration limit was reached.
+fn find_solution(ilp:&ILP, graph:&mut VectorDiGraph, options:&SolverOptions, stopped:Option<ILPError>, start:Instant, progress:&mut dyn FnMut(ProgressEvent)) -> Result<(Vector, SolutionPath, bool), ILPError> {
+    if graph.get_node_by_vec(&ilp.b).is_none() {
+        if let Some(ILPError::Timeout) = stopped {
+            if let Some(x) = heuristics::greedy_solve(ilp) {
+                log!(" -> Time limit, returning heuristic solution (not proven optimal).");
+                let path = columns_path(ilp, &x);
+                return Ok((x, path, false));
+            }
+        }
+        return Err(stopped.unwrap_or(ILPError::NoSolution));
+    }
+
+    let optimal = if let Some(ILPError::ResourceExhausted) = stopped {
+        return Err(ILPError::ResourceExhausted);
+    } else if stopped.is_some() {
+        log!(" -> Time limit, returning the best known path (not proven optimal).");
+        false
+    } else if ilp.c.is_zero() && ilp.c2.iter().all(Vector::is_zero) {
+        // the construction already provides a path to b and all paths are optimal
+        log!(" -> The objective is zero, skipping Bellman-Ford Algorithm.");
+        true
+    } else if options.tiebreak == TieBreak::First && ilp.c2.is_none()
+        && ilp.objective_upper_bound() == graph.get_node_by_vec(&ilp.b).map(|node| node.cost) {
+        log!(" -> The path to b meets the objective upper bound, skipping Bellman-Ford Algorithm.");
+        true
+    } else {
+        longest_paths(ilp, graph, options, start, progress)?
+    };
+
+    if options.dump_costs {
+        dump_costs(graph);
+    }
+
+    let (x, path) = extract_solution(ilp, graph, &ilp.b, start)?;
+
+    // the greedy heuristic might know a better solution than the graph so far
+    if !optimal && options.time_limit_reached(start) {
+        if let Some(greedy) = heuristics::greedy_solve(ilp) {
+            if greedy.dot(&ilp.c) > x.dot(&ilp.c) {
+                log!(" -> Time limit, returning heuristic solution (not proven optimal).");
+                let path = columns_path(ilp, &greedy);
+                return Ok((greedy, path, false));
+            }
+        }
+    }
+
+    Ok((x, path, optimal))
+}
+
+/// A path to Ax for a solution that was not found in the graph: the columns
+/// in index order.
+fn columns_path(ilp:&ILP, x:&Vector) -> SolutionPath {
+    let mut cost = 0;
+
+    x.iter()
+        .enumerate()
+        .flat_map(|(j, &k)| std::iter::repeat_n(j, k as usize))
+        .map(|j| {
+            cost += ilp.c.data[j];
+            (j, cost)
+        })
+        .collect()
+}
+
+/// Solves the ILP for several right-hand sides. The graph is constructed
+/// only once (it covers the search regions of all targets). The longest
+/// paths start at the origin and do not depend on the target, thus the
+/// Bellman-Ford algorithm runs only once as well.
+pub fn solve_multi(ilp:&ILP, targets:&[Vector], options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> Vec<Result<Vector, ILPError>> {
+    log!("Solving ILP for {} targets with the Eisenbrand & Weismantel algorithm...", targets.len());
+    let start = Instant::now();
+
+    for b in targets.iter() {
+        assert_eq!(b.len(), ilp.b.len());
+    }
+
+    let target_refs:Vec<&Vector> = targets.iter().collect();
+    let (mut graph, stats, stopped) = construct_graph(ilp, &target_refs, options, start, progress);
+    #[cfg(debug_assertions)]
+    validate_graph(ilp, &graph);
+    let delta_b = targets.iter().map(|b| b.inf_norm()).max().unwrap();
+    let final_bound = options.bound_factor * radius(ilp, delta_b, stats.depth as i32);
+
+    let unbounded = if targets.iter().any(|b| graph.get_node_by_vec(b).is_some()) {
+        matches!(longest_paths(ilp, &mut graph, options, start, progress), Err(ILPError::Unbounded))
+    } else {
+        false
+    };
+
+    targets.iter().map(|b| {
+        log!(" -> Target b = {:?}", b);
+
+        if graph.get_node_by_vec(b).is_none() {
+            match stopped {
+                Some(ILPError::ResourceExhausted) => return Err(ILPError::ResourceExhausted),
+                Some(_) => return Err(ILPError::Timeout),
+                None => {}
+            }
+            explain_no_solution(ilp, &graph, b, final_bound);
+            return Err(ILPError::NoSolution);
+        }
+
+        // b is reachable and so is a positive cycle
+        if unbounded {
+            return Err(ILPError::Unbounded);
+        }
+
+        extract_solution(ilp, &graph, b, start).map(|(x, _)| x)
+    }).collect()
+}
+
+/// Constructs the graph of `solve_multi` for the given targets, the longest
+/// paths can then be found with `solve_on_graph`. Fails if the construction
+/// stopped at the time or memory limit.
+pub fn build_graph(ilp:&ILP, targets:&[Vector], options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> Result<VectorDiGraph, ILPError> {
+    for b in targets.iter() {
+        assert_eq!(b.len(), ilp.b.len());
+    }
+
+    let target_refs:Vec<&Vector> = targets.iter().collect();
+    let (graph, _, stopped) = construct_graph(ilp, &target_refs, options, Instant::now(), progress);
+    #[cfg(debug_assertions)]
+    validate_graph(ilp, &graph);
+
+    match stopped {
+        Some(e) => Err(e),
+        None => Ok(graph)
+    }
+}
+
+/// Continues the Bellman-Ford algorithm on a graph of `build_graph` from its
+/// current path state and returns the longest path to b. The costs are taken
+/// from `ilp`, which has to have the matrix the graph was built for. If c
+/// changed, `VectorDiGraph::reset_path_state` has to be called before.
+pub fn solve_on_graph(ilp:&ILP, graph:&mut VectorDiGraph, b:&Vector, options:&SolverOptions, progress:&mut dyn FnMut(ProgressEvent)) -> Result<Vector, ILPError> {
+    let start = Instant::now();
+
+    if graph.get_node_by_vec(b).is_none() {
+        return Err(ILPError::NoSolution);
+    }
+
+    // the Bellman-Ford iterations skip the origin
+    relax_edges(ilp, graph, 0, options.tiebreak);
+    longest_paths(ilp, graph, options, start, progress)?;
+    extract_solution(ilp, graph, b, start).map(|(x, _)| x)
+}
+
+/// Constructs the graph of all integer points that can be reached from
+/// the origin by adding columns of A while staying close to the line
+/// segment between the origin and (at least) one of the targets.
+/// The construction stops early if the time limit (`Timeout`) or the memory
+/// limit (`ResourceExhausted`) is reached, the reason is returned.
+fn construct_graph(ilp:&ILP, targets:&[&Vector], options:&SolverOptions, start:Instant, progress:&mut dyn FnMut(ProgressEvent)) -> (VectorDiGraph, SolveStats, Option<ILPError>) {
+    if options.bound_factor < 1.0 {
+        log!(" -> Warning: bound factor {} < 1, solutions might be missed!", options.bound_factor);
+    }
+
     // constants
-    let r = 1.0 / ilp.b.norm2() as f32;
     let (rows, columns) = ilp.A.size; // (m,n)
-    let b_float = ilp.b.as_f32_vec();
+    let delta_b = targets.iter().map(|b| b.inf_norm()).max().unwrap();
+    // (b, b as floats, 1/‖b‖²), see `is_in_bounds` for the precision
+    let targets:Vec<(&Vector, Vec<f64>, f64)> = targets.iter()
+        .map(|&b| {
+            let b_float = b.as_f64_vec();
+            let r = inv_norm2(&b_float);
+            (b, b_float, r)
+        })
+        .collect();
+
+    // if the path to b meets this bound it is optimal and the construction can stop
+    // (not if ties are broken, other optimal paths might be preferred)
+    let early_stop_bound = match targets.as_slice() {
+        [(b, _, _)] if *b == &ilp.b && options.tiebreak == TieBreak::First && ilp.c2.is_none() => ilp.objective_upper_bound(),
+        _ => None
+    };
 
     // graph
-    let mut graph = VectorDiGraph::with_capacity(16384, columns);
+    let capacity = match options.node_capacity {
+        Some(capacity) => capacity,
+        None => initial_capacity(ilp)
+    };
+    let mut graph = VectorDiGraph::with_capacity(capacity, columns);
+
+    // adding sparse columns only touches their non-zero entries
+    let sparse_columns = if ilp.A.density() < SPARSE_DENSITY {
+        Some(ilp.A.sparse_columns())
+    } else {
+        None
+    };
 
-    // construction surface
-    let mut surface:Vec<(Vector, NodeIdx)> = Vec::with_capacity(16384);
-    let mut new_surface:Vec<(Vector, NodeIdx)> = Vec::with_capacity(16384);
+    // construction surface (grows as needed)
+    let surface_capacity = usize::min(capacity, 16384);
+    let mut surface:Vec<(Vector, NodeIdx)> = Vec::with_capacity(surface_capacity);
+    let mut new_surface:Vec<(Vector, NodeIdx)> = Vec::with_capacity(surface_capacity);
     let mut max_surface_size = 1;
+    let mut surface_sizes = Vec::new();
+    let mut merged = 0; // candidates that were already in the graph
+    let mut candidates = 0; // points x + A_i that were examined
+    let mut accepted = 0; // candidates within the search region
+    let mut next_memory_report = MEMORY_REPORT_START;
 
     // add origin
     {
@@ -35,15 +295,21 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
     }
 
     // construct graph
-    print!(" -> Constructing the graph");
-    io::stdout().flush().ignore();
+    log_inline!(" -> Constructing the graph");
+
+    if options.explain {
+        log!();
+        log!("    Starting at 0, columns of A are added to the points of the last layer (surface).");
+        log!("    A new point x is kept if ||x - s*b|| <= radius where s*b is the closest point to x");
+        log!("    on the line segment from 0 to b. The radius shrinks with the depth.");
+    }
 
     let mut bound;
     let mut depth = 0;
     while !surface.is_empty() {
-        print!(".");
-        io::stdout().flush().ignore();
-        
+        let (size_before, merged_before, edges_before) = (graph.size(), merged, graph.num_edges());
+        let expanded = surface.len();
+
         // pre-allocate memory for new nodes
         let max_new_nodes = surface.len() * columns;
         graph.reserve(max_new_nodes);
@@ -51,29 +317,50 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
 
         // grow graph
         depth = depth+1;
-        bound = compute_bound(ilp, depth);
-        for (x, node_idx) in surface.drain(0..surface.len()) {
+        bound = options.bound_factor * radius(ilp, delta_b, depth);
+        for (mut x, node_idx) in surface.drain(0..surface.len()) {
             let from = graph.get(node_idx).clone();
 
             // iterate over matrix columns
             for (i, (v,&c)) in ilp.A.iter().zip(ilp.c.iter()).enumerate() {
-                // potentially new point
-                let xp = x.add(v);
-                let s = clamp(xp.dot(&ilp.b) as f32 * r, 0.0, 1.0);
-
-                // ||xp - d*b|| <= bound
-                if is_in_bounds(&xp, &b_float, s, bound) {
+                // potentially new point, sparse columns are added to x in place
+                // (and subtracted again below) to avoid copying x for every candidate
+                let dense_xp;
+                let xp = match sparse_columns {
+                    Some(ref columns) => {
+                        columns[i].add_to(&mut x);
+                        &x
+                    },
+                    None => {
+                        dense_xp = x.add(v);
+                        &dense_xp
+                    }
+                };
+
+                // ||xp - d*b|| <= bound for any of the targets b
+                let in_bounds = targets.iter().any(|(_, b_float, r)| {
+                    let s = scale(xp, b_float, *r);
+                    is_in_bounds(xp, b_float, s, bound as f64)
+                });
+
+                candidates += 1;
+                if in_bounds {
+                    accepted += 1;
                     let cost = c as Cost;
                     let to_cost = from.cost + cost;
+                    let to_cost2 = from.cost2 + secondary_cost(ilp, i);
 
-                    let to_idx = match graph.get_node_by_vec_mut(&xp) {
+                    let to_idx = match graph.get_node_by_vec_mut(xp) {
                         Some(node) => {
                             // this vector was already in the graph
+                            merged += 1;
 
-                            // bellman-ford update 
-                            if to_cost > node.cost {
+                            // bellman-ford update
+                            if is_better(options.tiebreak, (to_cost, to_cost2), from.length + 1, node) {
                                 node.predecessor = from.idx;
                                 node.cost = to_cost;
+                                node.cost2 = to_cost2;
+                                node.length = from.length + 1;
                                 node.via = i as ColumnIdx;
                             }
 
@@ -82,16 +369,30 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
                         None => {
                             // add new node
                             let idx = graph.add_node(xp.clone(), from.idx, to_cost, i as ColumnIdx);
-                            new_surface.push((xp, idx));
+                            graph.get_mut(idx).cost2 = to_cost2;
+                            new_surface.push((xp.clone(), idx));
                             idx
                         }
                     };
 
                     graph.add_edge(from.idx, to_idx, i as ColumnIdx);
                 }
+
+                if let Some(ref columns) = sparse_columns {
+                    columns[i].subtract_from(&mut x);
+                }
             }
         }
 
+        if options.explain {
+            let candidates = expanded * columns;
+            let accepted = graph.num_edges() - edges_before;
+            log!("    depth {}: radius {}, {} surface points, {} candidates: {} new, {} merged, {} rejected",
+                depth, bound, expanded, candidates,
+                graph.size() - size_before, merged - merged_before, candidates - accepted
+            );
+        }
+
         // swap buffers (keep capacity/avoid new allocation)
         {
             let tmp = surface;
@@ -99,82 +400,393 @@ pub fn solve(ilp:&ILP) -> Result<Vector, ILPError> {
             new_surface = tmp;
         }
 
+        progress(ProgressEvent::GraphDepth { depth: depth as usize, surface: surface.len() });
+        surface_sizes.push(surface.len());
         if surface.len() > max_surface_size {
             max_surface_size = surface.len();
         }
-    }
 
-    println!();
-    println!(" -> Graph constructed! t={:?}", start.elapsed());
-    println!("    #vertices: {}, #edges: {}", graph.size(), graph.num_edges());
-    println!("    depth: {}, max. surface size: {}", depth, max_surface_size);
-    println!("    radius: start={} end={}", compute_bound(ilp, 1), compute_bound(ilp, depth));
+        let memory = graph.estimated_memory(rows);
+        if memory >= next_memory_report {
+            log!();
+            log!(" -> Estimated graph memory: {} MiB at depth {}", memory >> 20, depth);
+            while next_memory_report <= memory {
+                next_memory_report *= 2;
+            }
+        }
+
+        // the limits are only checked between depths (soft limits)
+        let stop = if surface.is_empty() {
+            None
+        } else if options.time_limit_reached(start) {
+            log!();
+            log!(" -> Time limit reached at depth {}!", depth);
+            Some(ILPError::Timeout)
+        } else if options.max_memory.iter().any(|&limit| memory > limit) {
+            log!();
+            log!(" -> Memory limit reached at depth {} (estimated {} MiB)!", depth, memory >> 20);
+            Some(ILPError::ResourceExhausted)
+        } else {
+            None
+        };
+
+        if stop.is_some() {
+            let stats = SolveStats {
+                vertices: graph.size(),
+                edges: graph.num_edges(),
+                depth: depth as usize,
+                surface_sizes,
+                max_surface_size,
+                candidates,
+                accepted,
+                merged,
+                boundary_vertices: 0
+            };
+            return (graph, stats, stop);
+        }
 
-    let b_node = match graph.get_node_by_vec(&ilp.b) {
-        Some(node) => node.clone(),
-        None => return Err(ILPError::NoSolution)
+        if let (Some(bound), Some(node)) = (early_stop_bound, graph.get_node_by_vec(&ilp.b)) {
+            if node.cost == bound {
+                log!();
+                log!(" -> Found a path to b with the objective upper bound {} at depth {}, stopping early.", bound, depth);
+                break;
+            }
+        }
+    }
+
+    log!();
+    log!(" -> Graph constructed! t={:?}", start.elapsed());
+    log!("    #vertices: {}, #edges: {}", graph.size(), graph.num_edges());
+    log!("    candidates: {}, accepted: {} ({:.1}%), merged: {}",
+        candidates, accepted, 100.0 * accepted as f64 / candidates as f64, merged
+    );
+    log!("    depth: {}, max. surface size: {}", depth, max_surface_size);
+    let final_bound = options.bound_factor * radius(ilp, delta_b, depth);
+    log!("    radius: start={} end={}",
+        options.bound_factor * radius(ilp, delta_b, 1),
+        final_bound
+    );
+    let boundary_vertices = count_boundary_nodes(&graph, &targets, final_bound);
+    log!("    vertices at the final radius: {}", boundary_vertices);
+
+    let stats = SolveStats {
+        vertices: graph.size(),
+        edges: graph.num_edges(),
+        depth: depth as usize,
+        surface_sizes,
+        max_surface_size,
+        candidates,
+        accepted,
+        merged,
+        boundary_vertices
     };
 
-    println!(" -> Continue Bellman-Ford Algorithm to find longest path...");
+    (graph, stats, None)
+}
+
+/// Checks (in debug builds) that the graph is consistent with how it was
+/// constructed: the origin is 0 and the vector of every node is the vector
+/// of its predecessor (and of every node with an edge to it) plus the column
+/// of that edge. By induction the vector of a node is the sum of the columns
+/// along its predecessor path.
+#[cfg(debug_assertions)]
+fn validate_graph(ilp:&ILP, graph:&VectorDiGraph) {
+    let vectors = graph.vectors_by_idx();
+    assert!(vectors[0].is_zero(), "the first node is not the origin");
+
+    for idx in graph.iter_nodes() {
+        let node = graph.get(idx);
+        assert_eq!(
+            vectors[node.predecessor].add(&ilp.A.columns[node.via]), *vectors[idx],
+            "node {} does not match its predecessor {} plus column {}", idx, node.predecessor, node.via
+        );
+    }
+
+    for idx in 0..graph.size() {
+        for &(to, column) in graph.get(idx).edges.iter() {
+            assert_eq!(
+                vectors[idx].add(&ilp.A.columns[column]), *vectors[to],
+                "edge {} -> {} does not match column {}", idx, to, column
+            );
+        }
+    }
+}
+
+/// Called if b is not in the (complete) graph. The graph contains a path to
+/// b (if Ax = b has a solution) only if the final search radius was at least
+/// the Steinitz bound m * Δ. Reports how close the vertices reachable from
+/// 0 came to b, i.e. where the search ended.
+fn explain_no_solution(ilp:&ILP, graph:&VectorDiGraph, b:&Vector, bound:f32) {
+    let steinitz = converged_radius(ilp);
+    let closest = graph.iter_vectors().map(|v| v.linf_distance(b)).min();
+    let near = graph.iter_vectors().filter(|v| v.linf_distance(b) as f32 <= bound).count();
+
+    match closest {
+        Some(distance) => log!(
+            " -> {} of the {} vertices reachable from 0 are within distance {} of b, the closest at distance {}.",
+            near, graph.size(), bound, distance
+        ),
+        None => log!(" -> No vertex is reachable from 0.")
+    }
+
+    if bound < steinitz {
+        log!(" -> Warning: the final search radius {} is below the Steinitz bound m*\u{0394} = {},", bound, steinitz);
+        log!("    the ILP might have a solution!");
+    } else {
+        log!(" -> b was within search bounds, instance is infeasible.");
+    }
+}
+
+/// Counts the vertices in the outermost layer of the final search region, i.e.
+/// vertices within distance `bound` but not within `bound - 1` of the targets.
+/// This indicates how tight the search region is around the graph.
+fn count_boundary_nodes(graph:&VectorDiGraph, targets:&[(&Vector, Vec<f64>, f64)], bound:f32) -> usize {
+    let within = |v:&Vector, bound:f32| targets.iter().any(|(_, b_float, r)| {
+        let s = scale(v, b_float, *r);
+        is_in_bounds(v, b_float, s, bound as f64)
+    });
+
+    graph.iter_vectors()
+        .filter(|v| within(v, bound) && !within(v, bound - 1.0))
+        .count()
+}
+
+/// Continues the Bellman-Ford algorithm (for longest paths) on the graph.
+/// Returns false if the iteration or time limit was hit before convergence
+/// and an error if a positive cycle was found.
+fn longest_paths(ilp:&ILP, graph:&mut VectorDiGraph, options:&SolverOptions, start:Instant, progress:&mut dyn FnMut(ProgressEvent)) -> Result<bool, ILPError> {
+    log!(" -> Continue Bellman-Ford Algorithm to find longest path...");
+
+    if options.explain {
+        log!("    The cost of a node is the cost of the best known path from 0 to it.");
+        log!("    Edges are relaxed until no cost improves, a path to b then is optimal.");
+    }
     let mut iterations = 0;
-    // scan up to |V| - 2 times
-    for _ in 2..graph.size() {
-        let mut changed = false;
+    let mut changed = true;
+    let max_iterations = match options.max_bf_iterations {
+        Some(max) => usize::min(max, graph.size().saturating_sub(1)),
+        None => graph.size().saturating_sub(1) // scan up to |V| - 1 times
+    };
+
+    while changed && iterations < max_iterations {
+        changed = false;
         iterations += 1;
 
         for node_idx in graph.iter_nodes() {
-            let node = graph.get(node_idx).clone();
-            for &(to, column) in node.edges.iter() {
-                let to_cost = node.cost + ilp.c.data[column];
-                let to_node = graph.get_mut(to);
+            changed |= relax_edges(ilp, graph, node_idx, options.tiebreak);
+        }
+        progress(ProgressEvent::BellmanFordIter { n: iterations });
 
-                if to_cost > to_node.cost {
-                    to_node.predecessor = node.idx;
-                    to_node.cost = to_cost;
-                    to_node.via = column;
+        if changed && options.time_limit_reached(start) {
+            log!(" -> Time limit, stopping the Bellman-Ford Algorithm.");
+            return Ok(false);
+        }
+    }
 
-                    changed = true;
-                }
-            }
+    log!(" -> {} Bellman-Ford iterations, t={:?}", iterations, start.elapsed());
+
+    // a cap of |V| - 1 (or more) does not limit the algorithm
+    let limited = max_iterations < graph.size().saturating_sub(1);
+    if changed && limited {
+        log!(" -> Warning: iteration limit reached, the solution is not proven to be optimal!");
+        return Ok(false);
+    }
+
+    // After |V| - 1 iterations every longest simple path is known. If an edge
+    // can still be relaxed there is a positive cycle reachable from the origin
+    // (b is reachable as well, so the ILP is unbounded) even if that cycle is
+    // not on the current path to b.
+    if changed && has_positive_cycle(ilp, graph) {
+        log!(" -> Found a positive cycle.");
+        return Err(ILPError::Unbounded);
+    }
+
+    Ok(true)
+}
+
+/// Prints the vector and cost of every node (at most `MAX_DUMP_NODES`).
+fn dump_costs(graph:&VectorDiGraph) {
+    log!(" -> Node costs:");
+
+    for (idx, v) in graph.vectors_by_idx().iter().enumerate().take(MAX_DUMP_NODES) {
+        match graph.get(idx).cost {
+            UNREACHED => log!("    {:?}: unreached", v),
+            cost => log!("    {:?}: {}", v, cost)
         }
+    }
 
-        if !changed {
-            break;
+    if graph.size() > MAX_DUMP_NODES {
+        log!("    ... ({} more nodes)", graph.size() - MAX_DUMP_NODES);
+    }
+}
+
+/// Returns true if any edge can still be relaxed (without relaxing it).
+fn has_positive_cycle(ilp:&ILP, graph:&VectorDiGraph) -> bool {
+    graph.iter_nodes().any(|node_idx| {
+        let node = graph.get(node_idx);
+
+        node.cost != UNREACHED && node.edges.iter().any(|&(to, column)| {
+            let to_node = graph.get(to);
+            let to_cost = (node.cost + ilp.c.data[column], node.cost2 + secondary_cost(ilp, column));
+
+            to_cost > (to_node.cost, to_node.cost2)
+        })
+    })
+}
+
+/// Relaxes the outgoing edges of a node, returns true if a cost changed.
+fn relax_edges(ilp:&ILP, graph:&mut VectorDiGraph, node_idx:NodeIdx, tiebreak:TieBreak) -> bool {
+    let node = graph.get(node_idx).clone();
+    let mut changed = false;
+
+    if node.cost == UNREACHED {
+        return false;
+    }
+
+    for &(to, column) in node.edges.iter() {
+        let to_cost = (node.cost + ilp.c.data[column], node.cost2 + secondary_cost(ilp, column));
+        let to_node = graph.get_mut(to);
+
+        if is_better(tiebreak, to_cost, node.length + 1, to_node) {
+            to_node.predecessor = node.idx;
+            to_node.cost = to_cost.0;
+            to_node.cost2 = to_cost.1;
+            to_node.length = node.length + 1;
+            to_node.via = column;
+
+            changed = true;
         }
     }
 
-    println!(" -> {} Bellman-Ford iterations, t={:?}", iterations, start.elapsed());
-    println!(" -> Longest path cost: {}", b_node.cost);
+    changed
+}
+
+/// Returns true if a path with the given (primary, secondary) cost and length
+/// is better than the current path to the node. The costs are compared
+/// lexicographically, with `TieBreak::MinL1` shorter paths (smaller ‖x‖₁)
+/// win among paths of equal cost.
+fn is_better(tiebreak:TieBreak, cost:(Cost, Cost), length:usize, node:&Node) -> bool {
+    let node_cost = (node.cost, node.cost2);
+
+    match tiebreak {
+        TieBreak::First | TieBreak::MinSupport => cost > node_cost,
+        TieBreak::MinL1 => cost > node_cost || (cost == node_cost && length < node.length)
+    }
+}
+
+/// Cost of a column for the secondary objective (0 if there is none).
+fn secondary_cost(ilp:&ILP, column:usize) -> Cost {
+    ilp.c2.as_ref().map_or(0, |c2| c2.data[column])
+}
+
+/// Follows the predecessors from b back to the origin. The graph is not
+/// modified, so this can be used for several targets.
+fn extract_solution(ilp:&ILP, graph:&VectorDiGraph, b:&Vector, start:Instant) -> Result<(Vector, SolutionPath), ILPError> {
+    let columns = ilp.A.size.1;
+    let mut node = graph.get_node_by_vec(b).unwrap();
+    log!(" -> Longest path cost: {}", node.cost);
 
     // create solution vector
-    println!(" -> Creating solution vector... t={:?}", start.elapsed());
+    log!(" -> Creating solution vector... t={:?}", start.elapsed());
+
+    // the origin has cost 0 unless it is on a positive cycle (this matters
+    // for b = 0, where the loop below stops immediately)
+    if graph.get(0).cost > 0 {
+        return Err(ILPError::Unbounded);
+    }
 
     let mut x = Vector::zero(columns);
-    let b_idx = b_node.idx;
-    let mut node = graph.get_node_by_vec_mut(&ilp.b).unwrap();
+    let mut columns_used = Vec::new();
 
     // start from b and go backwards to 0
-    loop {
-        let pre = node.predecessor;
-
-        if pre == b_idx {
+    while node.idx != 0 {
+        // a simple path has less than |V| edges, otherwise the predecessors form a cycle
+        if columns_used.len() >= graph.size() {
             return Err(ILPError::Unbounded);
-        } else {
-            // mark node as visited
-            node.predecessor = b_idx;
         }
 
         x.data[node.via as usize] += 1;
-        node = graph.get_mut(pre);
+        columns_used.push(node.via);
+        node = graph.get(node.predecessor);
+    }
+
+    // the columns were collected from b to 0
+    let mut cost = 0;
+    let path = columns_used.into_iter()
+        .rev()
+        .map(|j| {
+            cost += ilp.c.data[j];
+            (j, cost)
+        })
+        .collect();
+
+    log!(" -> Done! Time elapsed: {:?}", start.elapsed());
+
+    Ok((x, path))
+}
+
+/// Rough estimate of the number of graph vertices: the lattice points
+/// within the initial radius (inf-norm) of the segment between 0 and b.
+pub fn estimate_graph_size(ilp:&ILP) -> f64 {
+    let m = ilp.A.size.0 as i32;
+    let side = 2.0 * initial_radius(ilp).floor() as f64 + 1.0;
+
+    side.powi(m-1) * (ilp.delta_b as f64 + side)
+}
+
+/// Number of lattice points x in the search region at the given depth:
+/// ||x - s*b||∞ <= radius where s*b is the point of the segment from 0 to b
+/// closest to x (the graph contains the reachable ones). Counted exactly
+/// if the bounding box of the region is small, estimated like
+/// `estimate_graph_size` otherwise.
+pub fn count_region_points(ilp:&ILP, depth:i32) -> f64 {
+    let bound = radius_at_depth(ilp, depth) as f64;
+    let r = bound.floor() as IntData;
+    let lower:Vec<IntData> = ilp.b.iter().map(|&b| b.min(0) - r).collect();
+    let upper:Vec<IntData> = ilp.b.iter().map(|&b| b.max(0) + r).collect();
+    let box_size:f64 = lower.iter().zip(upper.iter()).map(|(&l, &u)| (u - l + 1) as f64).product();
+
+    if box_size > MAX_REGION_ENUMERATION {
+        let m = ilp.A.size.0 as i32;
+        let side = 2.0 * r as f64 + 1.0;
+        return side.powi(m-1) * (ilp.delta_b as f64 + side);
+    }
 
-        if node.idx == 0 {
+    let b_float = ilp.b.as_f64_vec();
+    let r = inv_norm2(&b_float);
+    let mut x = Vector::from_slice(&lower);
+    let mut count = 0;
+
+    loop {
+        let s = scale(&x, &b_float, r);
+        if is_in_bounds(&x, &b_float, s, bound) {
+            count += 1;
+        }
+
+        // next x (odometer order)
+        let mut i = 0;
+        while i < x.len() && x.data[i] == upper[i] {
+            x.data[i] = lower[i];
+            i += 1;
+        }
+
+        if i == x.len() {
             break;
         }
+
+        x.data[i] += 1;
     }
 
-    println!(" -> Done! Time elapsed: {:?}", start.elapsed());
+    count as f64
+}
+
+/// Initial node capacity of the graph based on the estimated graph size.
+/// Small instances do not need the memory and for large instances the
+/// estimate is way too pessimistic, thus it is clamped to a sane range.
+fn initial_capacity(ilp:&ILP) -> usize {
+    let estimate = estimate_graph_size(ilp);
 
-    Ok(x)
+    clamp(estimate, 256.0, (1 << 20) as f64) as usize
 }
 
 fn clamp<T: Float>(x:T, min: T, max: T) -> T {
@@ -183,10 +795,27 @@ fn clamp<T: Float>(x:T, min: T, max: T) -> T {
     T::min(T::max(min, x), max)
 }
 
-fn compute_bound(ilp:&ILP, depth:i32) -> f32 {
+/// Radius (inf-norm) of the search region around the segment from 0 to b
+/// at the given depth: m * min(2Δ, Δ + ‖b‖/depth).
+pub fn radius_at_depth(ilp:&ILP, depth:i32) -> f32 {
+    radius(ilp, ilp.delta_b, depth)
+}
+
+/// Radius of the search region at depth 1 (the largest radius).
+pub fn initial_radius(ilp:&ILP) -> f32 {
+    radius_at_depth(ilp, 1)
+}
+
+/// Limit of the radius for large depths: m * Δ. Note that the radius
+/// starts at m * 2Δ (at most) and decreases towards this value.
+pub fn converged_radius(ilp:&ILP) -> f32 {
+    (ilp.A.size.0 as IntData * ilp.delta_A) as f32
+}
+
+fn radius(ilp:&ILP, delta_b:IntData, depth:i32) -> f32 {
     let (m,_) = ilp.A.size;
     let da = ilp.delta_A as f32;
-    let db = ilp.delta_b as f32;
+    let db = delta_b as f32;
     let delta = f32::min(
         2.0 * da,
         da + (1.0/depth as f32) * db
@@ -194,13 +823,33 @@ fn compute_bound(ilp:&ILP, depth:i32) -> f32 {
     delta * m as f32
 }
 
+/// 1/‖b‖² (0 for b = 0), computed with f64 as ‖b‖² exceeds the i32 range
+/// for ‖b‖ > 46340.
+fn inv_norm2(b:&[f64]) -> f64 {
+    let norm2:f64 = b.iter().map(|x| x * x).sum();
+
+    if norm2 == 0.0 { 0.0 } else { 1.0 / norm2 }
+}
+
+/// The scale s in [0,1] of the point s*b closest to v, i.e. <v,b>/‖b‖²
+/// clamped to the segment from 0 to b (`r` is 1/‖b‖²).
+fn scale(v:&Vector, b:&[f64], r:f64) -> f64 {
+    let dot:f64 = v.iter().zip(b.iter()).map(|(&x, &b)| x as f64 * b).sum();
+
+    clamp(dot * r, 0.0, 1.0)
+}
+
 /// ||x - s*b||_{inf} <= bound
-fn is_in_bounds(v:&Vector, b:&Vec<f32>, s:f32, bound:f32) -> bool {
+///
+/// Computed with f64: for large b the scale s = <x,b>/‖b‖² needs more than
+/// the 24 bit mantissa of f32 (s*b would be off by more than 1 for
+/// ‖b‖ > 2^24 and points near the boundary could be wrongly rejected).
+fn is_in_bounds(v:&Vector, b:&[f64], s:f64, bound:f64) -> bool {
     debug_assert!(v.len() == b.len());
 
     for (&x,&b) in v.iter().zip(b.iter()) {
-        let d = (x as f32 - (s * b)).abs();
-        
+        let d = (x as f64 - (s * b)).abs();
+
         if d > bound {
             return false;
         }
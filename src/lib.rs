@@ -0,0 +1,38 @@
+//! Solvers for integer linear programs max { c*x | Ax=b, x>=0, x integral }.
+//!
+//! The most common types are re-exported at the crate root:
+//!
+//! ```
+//! use intopt::{IntData, Cost, Vector};
+//!
+//! let entries:[IntData; 3] = [3, -1, 4];
+//! let v = Vector::from_slice(&entries);
+//! let norm:Cost = v.one_norm();
+//! assert_eq!(v.len(), 3);
+//! assert_eq!(norm, 8);
+//! ```
+//!
+//! This includes the types to build an ILP, e.g. max x1 + x2 s.t. 2x1 + 4x2 = 7:
+//!
+//! ```
+//! use intopt::{ILP, ILPError, Matrix, Vector, VarMapping};
+//!
+//! let vars:Vec<VarMapping> = vec![("x1".to_string(), 0), ("x2".to_string(), 1)];
+//! let ilp = ILP::with_named_vars(
+//!     Matrix::from_slice(1, 2, &[2, 4]),
+//!     Vector::from_slice(&[7]),
+//!     Vector::from_slice(&[1, 1]),
+//!     vars
+//! ).unwrap();
+//! assert_eq!(ilp.variable_names(), ["x1", "x2"]);
+//! // 2x1 + 4x2 is even
+//! assert!(matches!(ilp.reduce_row_gcd(), Err(ILPError::NoSolution)));
+//! ```
+
+extern crate pest;
+#[macro_use] extern crate pest_derive;
+#[macro_use] extern crate matches;
+
+#[macro_use] pub mod ilp;
+
+pub use ilp::{IntData, Cost, VarMapping, Vector, Matrix, ILP, ILPError, Diagnostic, Severity};
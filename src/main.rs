@@ -1,10 +1,8 @@
-extern crate pest;
-#[macro_use] extern crate pest_derive;
-#[macro_use] extern crate matches;
-
-pub mod ilp;
-use ilp::*;
+use intopt::{log, log_inline};
+use intopt::ilp;
+use intopt::ilp::*;
 use clap::{App, Arg};
+use std::time::{Duration, Instant};
 
 fn main() {
     let matches = App::new("IntOpt ILP Solver")
@@ -23,13 +21,183 @@ fn main() {
                 .value_name("ALGORITHM")
                 .default_value("ew")
                 .hide_default_value(true)
-                .possible_values(&["ew", "jr"])
+                .possible_values(&["ew", "jr", "kp"])
                 .hide_possible_values(true)
                 .help("Sets the algorithm to solve the ILP with.\n\
                     ew for Eisenbrand & Weismantel (default)\n\
-                    jr for Jansen & Rohwedder")
+                    jr for Jansen & Rohwedder\n\
+                    kp for dynamic programming (knapsack problems only,\n\
+                    used by default if the ILP is a knapsack problem)")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("bound-factor")
+                .long("bound-factor")
+                .value_name("FACTOR")
+                .help("Scales the search radius of the ew algorithm (default 1).\n\
+                    Smaller values are faster but might miss the optimum.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("max-bf-iters")
+                .long("max-bf-iters")
+                .value_name("N")
+                .help("Limits the Bellman-Ford iterations of the ew algorithm.\n\
+                    The solution might not be optimal if the limit is reached.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("max-var-value")
+                .long("max-var-value")
+                .value_name("V")
+                .help("Caps the 1-norm of the partial solutions of the jr algorithm.\n\
+                    The solution might be missed if the cap is too small.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("max-memory")
+                .long("max-memory")
+                .value_name("MB")
+                .help("Stops the ew algorithm if the estimated graph memory exceeds this limit")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("print-path")
+                .long("print-path")
+                .help("Prints the columns (and the cost so far) along the optimal path (ew only)")
+        )
+        .arg(
+            Arg::with_name("dump-costs")
+                .long("dump-costs")
+                .help("Prints the cost of every graph node after the longest path search (ew only)")
+        )
+        .arg(
+            Arg::with_name("explain")
+                .long("explain")
+                .help("Narrates the steps of the algorithm")
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("Prints the most efficient columns and the cost of intermediate solutions (jr only)")
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .value_name("SECONDS")
+                .help("Stops the solver after the given time and returns the best solution found so far")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("fix")
+                .long("fix")
+                .value_name("NAME=VALUE")
+                .help("Fixes a variable to the given value")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+        )
+        .arg(
+            Arg::with_name("no-preprocess")
+                .long("no-preprocess")
+                .help("Solves the ILP as given, without preprocessing")
+        )
+        .arg(
+            Arg::with_name("objective-cap")
+                .long("objective-cap")
+                .value_name("B")
+                .help("Adds the constraint objective <= B")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+        )
+        .arg(
+            Arg::with_name("standard-form")
+                .long("standard-form")
+                .help("Solves A, b and c exactly as given (equations only, no slack\n\
+                    variables are added and the ILP is not preprocessed)")
+        )
+        .arg(
+            Arg::with_name("disable-pass")
+                .long("disable-pass")
+                .value_name("PASS")
+                .help("Disables a preprocessing pass")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .possible_values(&PREPROCESS_PASSES)
+        )
+        .arg(
+            Arg::with_name("tiebreak")
+                .long("tiebreak")
+                .value_name("RULE")
+                .help("Which optimal solution to return if there are several")
+                .takes_value(true)
+                .possible_values(&["first", "min-l1", "min-support"])
+                .default_value("first")
+        )
+        .arg(
+            Arg::with_name("decompose")
+                .long("decompose")
+                .help("Solves independent blocks of the ILP separately")
+        )
+        .arg(
+            Arg::with_name("duals")
+                .long("duals")
+                .help("Prints the solution and dual values of the LP relaxation")
+        )
+        .arg(
+            Arg::with_name("recommend")
+                .long("recommend")
+                .help("Prints the recommended algorithm for the ILP and exits")
+        )
+        .arg(
+            Arg::with_name("cone-check")
+                .long("cone-check")
+                .help("Checks with the (floating point) LP relaxation whether b is in the\n\
+                    cone spanned by the columns of A before solving")
+        )
+        .arg(
+            Arg::with_name("heuristic-restarts")
+                .long("heuristic-restarts")
+                .value_name("N")
+                .help("Runs the greedy heuristic (and N randomized restarts) before solving\n\
+                    and prints the best solution it found")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("dry-run")
+                .long("dry-run")
+                .help("Prints size estimates for both algorithms and exits without solving")
+        )
+        .arg(
+            Arg::with_name("print-ilp")
+                .long("print-ilp")
+                .help("Prints the (preprocessed) model in the .ilp format")
+        )
+        .arg(
+            Arg::with_name("sense")
+                .long("sense")
+                .value_name("SENSE")
+                .help("Overrides the objective sense of the file")
+                .takes_value(true)
+                .possible_values(&["min", "max"])
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Output format of the solution, assign prints only\n\
+                    name=value lines to stdout (the log goes to stderr)")
+                .takes_value(true)
+                .possible_values(&["text", "assign"])
+                .default_value("text")
+        )
+        .arg(
+            Arg::with_name("timings-json")
+                .long("timings-json")
+                .help("Prints the duration of every phase as JSON lines to stderr")
+        )
         .arg(
             Arg::with_name("input")
                 .takes_value(true)
@@ -39,30 +207,293 @@ fn main() {
         )
         .get_matches();
 
-    let mut ilp = parser::parse_file(matches.value_of("input").unwrap()).unwrap();
+    let mut timings = Timings::new(matches.is_present("timings-json"));
+    let assign = matches.value_of("format") == Some("assign");
+    ilp::log::to_stderr(assign);
 
-    if ilp.A.has_duplicate_columns() {
-        println!(" -> The matrix has duplicate columns!");
-        ilp = ilp.simplify();
-        println!();
+    let mut ilp = match parser::parse_file(matches.value_of("input").unwrap()) {
+        Ok(ilp) => ilp,
+        Err(e) => {
+            log!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match matches.value_of("sense") {
+        Some("min") => ilp.set_sense(Sense::Minimize),
+        Some("max") => ilp.set_sense(Sense::Maximize),
+        _ => {}
+    }
+
+    if let Some(assignments) = matches.values_of("fix") {
+        for assignment in assignments {
+            let (var, value) = match parse_assignment(assignment) {
+                Some(assignment) => assignment,
+                None => {
+                    log!("Invalid assignment {}, expected NAME=VALUE with an integer VALUE.", assignment);
+                    std::process::exit(1);
+                }
+            };
+
+            ilp = match ilp.fix_variable(var, value) {
+                Ok(ilp) => ilp,
+                Err(ILPError::UnknownVariable(name)) => {
+                    log!("Unknown variable {}.", name);
+                    std::process::exit(1);
+                },
+                Err(_) => {
+                    log!("The ILP has no solution.");
+                    return;
+                }
+            };
+        }
+    }
+
+    timings.phase("parse");
+
+    let standard_form = matches.is_present("standard-form");
+    if standard_form && ilp.num_slack_variables() > 0 {
+        log!("The ILP is not in standard form: it has {} inequalities or bounds (slack variables).", ilp.num_slack_variables());
+        std::process::exit(1);
     }
 
+    if let Some(cap) = matches.value_of("objective-cap") {
+        ilp = ilp.add_objective_cap(cap.parse().expect("invalid objective cap"));
+    }
+
+    if !matches.is_present("no-preprocess") && !standard_form {
+        let mut preprocess = PreprocessOptions::default();
+
+        if let Some(passes) = matches.values_of("disable-pass") {
+            for pass in passes {
+                preprocess.disable(pass);
+            }
+        }
+
+        ilp = match ilp.preprocess(preprocess) {
+            Ok(ilp) => ilp,
+            Err(_) => {
+                log!("The ILP has no solution.");
+                return;
+            }
+        };
+    }
+
+    timings.phase("preprocess");
     ilp.print_details();
+    if matches.is_present("verbose") {
+        ilp.print_column_efficiency(5);
+        log!();
+    }
+
+    if matches.is_present("dry-run") {
+        log!("Estimates:");
+        log!(" -> ew: graph size {:.1e}, radius {} (depth 1) -> {} (converged)",
+            steinitz::estimate_graph_size(&ilp),
+            steinitz::initial_radius(&ilp),
+            steinitz::converged_radius(&ilp)
+        );
+        log!(" -> ew: {:.1e} lattice points in the search region (depth 1)",
+            steinitz::count_region_points(&ilp, 1)
+        );
+        log!(" -> jr: K = {}, table size {}",
+            discrepancy::compute_K(&ilp),
+            discrepancy::estimate_table_size(&ilp)
+        );
+        // c*x <= floor(LP optimum) for every integer solution x
+        match relaxation::solve(&ilp) {
+            Ok(lp) => {
+                let bound = (lp.objective + 1e-6).floor();
+                let scale = ilp.objective_scale() as f64;
+                match ilp.sense() {
+                    Sense::Maximize => log!("LP relaxation optimum: {} (integer optimum <= {})", lp.objective / scale, bound / scale),
+                    Sense::Minimize => log!("LP relaxation optimum: {} (integer optimum >= {})", -lp.objective / scale, -bound / scale)
+                }
+            },
+            Err(ILPError::Unbounded) => log!("The LP relaxation is unbounded."),
+            Err(_) => log!("The LP relaxation has no solution.")
+        }
+        let (algorithm, reason) = ilp.recommend_algorithm();
+        log!("Recommending {}: {}", algorithm, reason);
+        return;
+    }
+
+    if matches.is_present("recommend") {
+        let (algorithm, reason) = ilp.recommend_algorithm();
+        log!("Recommending {}: {}", algorithm, reason);
+        return;
+    }
+
+    if matches.is_present("duals") {
+        match relaxation::solve(&ilp) {
+            Ok(lp) => {
+                log!("LP relaxation: c*x = {}", lp.objective);
+                log!(" -> x = {:?}", lp.x);
+                log!(" -> duals = {:?}", lp.duals);
+            },
+            Err(ILPError::Unbounded) => log!("The LP relaxation is unbounded."),
+            Err(_) => log!("The LP relaxation has no solution.")
+        }
+        log!();
+    }
+
+    if matches.is_present("print-ilp") {
+        log!("{}", ilp.to_ilp_string());
+    }
+
+    let mut options = SolverOptions::default();
+
+    if let Some(factor) = matches.value_of("bound-factor") {
+        options.bound_factor = factor.parse().expect("invalid bound factor");
+        assert!(options.bound_factor > 0.0, "the bound factor has to be positive");
+    }
+
+    if let Some(n) = matches.value_of("max-bf-iters") {
+        options.max_bf_iterations = Some(n.parse().expect("invalid number of iterations"));
+    }
+
+    if let Some(cap) = matches.value_of("max-var-value") {
+        options.max_var_value = Some(cap.parse().expect("invalid value"));
+    }
+
+    if let Some(mb) = matches.value_of("max-memory") {
+        let mb:usize = mb.parse().expect("invalid memory limit");
+        options.max_memory = Some(mb << 20);
+    }
 
-    let res = match matches.value_of("algorithm") {
-        Some("ew") => steinitz::solve(&ilp),
-        Some("jr") => discrepancy::solve(&ilp),
+    options.explain = matches.is_present("explain");
+    options.dump_costs = matches.is_present("dump-costs");
+
+    match matches.value_of("tiebreak") {
+        Some("min-l1") => options.tiebreak = TieBreak::MinL1,
+        Some("min-support") => options.tiebreak = TieBreak::MinSupport,
+        _ => {}
+    }
+
+    if let Some(seconds) = matches.value_of("timeout") {
+        options.timeout = Some(Duration::from_secs_f64(seconds.parse().expect("invalid timeout")));
+    }
+
+    let explain = options.explain;
+    let verbose = matches.is_present("verbose");
+    let mut progress = |event| match event {
+        ProgressEvent::GraphDepth { .. } if !explain => {
+            log_inline!(".");
+        },
+        ProgressEvent::TableSize { size } => log!("    > size: {}", size),
+        ProgressEvent::Incumbent { cost } if verbose => log!("    > solution for b with cost {}", cost),
+        _ => {}
+    };
+
+    // the -a option takes precedence over the solver: directive of the file
+    let explicit_algorithm = matches.occurrences_of("algorithm") > 0 || ilp.algorithm().is_some();
+    let algorithm = match ilp.algorithm() {
+        Some(algorithm) if matches.occurrences_of("algorithm") == 0 => {
+            log!("Using the algorithm {} selected by the file.", algorithm);
+            algorithm.to_string()
+        },
+        _ => matches.value_of("algorithm").unwrap().to_string()
+    };
+    let mut solve = |ilp:&ILP| match algorithm.as_str() {
+        "ew" if !explicit_algorithm && knapsack::applies(ilp) => knapsack::solve(ilp, &options),
+        "kp" if knapsack::applies(ilp) => knapsack::solve(ilp, &options),
+        "kp" => {
+            log!("The ILP is not a knapsack problem, using ew instead.");
+            steinitz::solve(ilp, &options, &mut progress)
+        },
+        "ew" if matches.is_present("print-path") => {
+            steinitz::solve_with_path(ilp, &options, &mut progress).map(|(x, path)| {
+                log!(" -> Path (column: cost so far):");
+                for (j, cost) in path {
+                    log!("    {}: {}", j, cost);
+                }
+                x
+            })
+        },
+        "ew" => steinitz::solve(ilp, &options, &mut progress),
+        "jr" => discrepancy::solve(ilp, &options, &mut progress),
         _ => panic!()
     };
 
-    println!();
+    // the LP relaxation is infeasible -> the ILP is infeasible (the simplex
+    // uses floats and can be wrong on ill-conditioned inputs, thus opt-in)
+    if matches.is_present("cone-check") && !ilp.in_conic_hull() {
+        log!("b is not in the cone spanned by the columns of A.");
+        log!("The ILP has no solution.");
+        return;
+    }
+
+    if let Some(n) = matches.value_of("heuristic-restarts") {
+        let restarts:usize = n.parse().expect("invalid number of restarts");
+        match heuristics::greedy_restarts(&ilp, restarts) {
+            Some(x) => log!("Greedy heuristic ({} restarts): objective value {}", restarts, ilp.objective_value(&x)),
+            None => log!("Greedy heuristic ({} restarts): no solution found", restarts)
+        }
+    }
+
+    timings.phase("details");
+    let res = if matches.is_present("decompose") {
+        ilp.solve_decomposed(solve)
+    } else {
+        solve(&ilp)
+    };
+    timings.phase("solve");
+
+    log!();
 
     match res {
+        Ok(x) if assign => ilp.print_assignment(&x),
         Ok(x) => {
-            println!("Solution:");
-            ilp.print_solution(&x)
+            log!("Solution:");
+            ilp.print_solution(&x);
+            log!("objective value: {}", ilp.objective_value(&x));
+
+            let support = x.nnz();
+            let bound = ilp.support_bound();
+            log!();
+            log!("support size: {} (an optimal solution with support <= {:.1} exists)", support, bound);
+            if support as f64 > bound {
+                log!("Warning: the support size exceeds the theoretical bound!");
+            }
         },
-        Err(ILPError::NoSolution) => println!("The ILP has no solution."),
-        Err(ILPError::Unbounded)  => println!("The ILP is unbounded.")
+        Err(ILPError::NoSolution) => log!("The ILP has no solution."),
+        Err(ILPError::Unbounded)  => log!("The ILP is unbounded."),
+        Err(ILPError::Timeout)    => log!("Time limit reached, no solution found."),
+        Err(ILPError::ResourceExhausted) => log!("Memory limit reached, no solution found."),
+        Err(ILPError::Overflow) => log!("Integer overflow, no solution found."),
+        Err(ILPError::UnknownVariable(name)) => log!("Unknown variable {}.", name),
+        Err(e @ ILPError::InvalidModel(_)) => log!("Error: {}.", e)
+    }
+}
+
+/// Splits a `--fix` assignment NAME=VALUE (None if it is invalid).
+fn parse_assignment(assignment:&str) -> Option<(&str, IntData)> {
+    let pos = assignment.find('=')?;
+    let value = assignment[pos+1..].trim().parse().ok()?;
+
+    Some((assignment[..pos].trim(), value))
+}
+
+/// Measures the duration of the phases (the time since the previous phase)
+/// and prints them as `{"phase":"solve","ms":1.5}` lines to stderr if enabled.
+struct Timings {
+    enabled: bool,
+    last: Instant
+}
+
+impl Timings {
+    fn new(enabled:bool) -> Self {
+        Timings { enabled, last: Instant::now() }
+    }
+
+    fn phase(&mut self, name:&str) {
+        let now = Instant::now();
+
+        if self.enabled {
+            let ms = now.duration_since(self.last).as_secs_f64() * 1000.0;
+            eprintln!("{{\"phase\":\"{}\",\"ms\":{:.3}}}", name, ms);
+        }
+
+        self.last = now;
     }
 }
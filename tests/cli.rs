@@ -0,0 +1,203 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Runs the command line interface on a file of tests/corpus, returns stdout and stderr.
+fn run(args:&[&str], file:&str) -> (String, String) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus").join(file);
+    let output = Command::new(env!("CARGO_BIN_EXE_intopt"))
+        .args(args)
+        .arg(path)
+        .output()
+        .expect("failed to run intopt");
+
+    assert!(output.status.success(), "intopt {:?} {} failed", args, file);
+    (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+}
+
+/// The objective value printed by the command line interface.
+fn objective_value(out:&str) -> &str {
+    out.lines()
+        .find_map(|line| line.strip_prefix("objective value: "))
+        .unwrap_or_else(|| panic!("no objective value in {}", out))
+}
+
+#[test]
+fn explain_narrates_both_algorithms() {
+    let (ew, _) = run(&["--explain", "-a", "ew"], "3x3.ilp");
+    assert!(ew.contains("columns of A are added to the points of the last layer"), "{}", ew);
+    assert!(ew.contains("depth 1: radius"), "{}", ew);
+
+    let (jr, _) = run(&["--explain", "-a", "jr"], "3x3.ilp");
+    assert!(jr.contains("The lookup table maps right-hand sides"), "{}", jr);
+    assert!(jr.contains("the table grew by"), "{}", jr);
+
+    let (quiet, _) = run(&["-a", "ew"], "3x3.ilp");
+    assert!(!quiet.contains("depth 1: radius"));
+}
+
+#[test]
+fn solution_lists_every_input_variable() {
+    // y and w are duplicate columns, simplify removes one of them
+    for algorithm in &["ew", "jr"] {
+        let (out, _) = run(&["-a", algorithm], "duplicate-columns.ilp");
+        let solution:Vec<&str> = out.lines()
+            .skip_while(|line| *line != "Solution:")
+            .skip(1)
+            .take_while(|line| !line.starts_with("objective value"))
+            .map(|line| line.split(" = ").next().unwrap().trim())
+            .collect();
+
+        assert_eq!(solution, ["x", "y", "z", "w"], "{}", out);
+    }
+}
+
+#[test]
+fn support_size_counts_the_non_zero_variables() {
+    // neither solution uses a slack variable
+    for &(file, expected) in &[("3x3.ilp", 3), ("duplicate-columns.ilp", 2)] {
+        let (out, _) = run(&[], file);
+        let non_zero = out.lines()
+            .skip_while(|line| *line != "Solution:")
+            .take_while(|line| !line.starts_with("objective value"))
+            .filter(|line| line.contains(" = ") && !line.ends_with(" = 0"))
+            .count();
+        let support:usize = out.lines()
+            .find_map(|line| line.strip_prefix("support size: "))
+            .and_then(|rest| rest.split(' ').next())
+            .expect("the support size is missing")
+            .parse()
+            .unwrap();
+
+        assert_eq!(support, expected, "{}", file);
+        assert_eq!(support, non_zero, "{}", file);
+        assert!(!out.contains("the support size exceeds the theoretical bound"));
+    }
+}
+
+#[test]
+fn infeasibility_is_explained() {
+    for algorithm in &["ew", "jr"] {
+        let (out, _) = run(&["-a", algorithm], "negative-solution.ilp");
+        assert!(out.contains("b was within search bounds, instance is infeasible."), "{}", out);
+        assert!(out.contains("The ILP has no solution."));
+    }
+
+    // a shrunk search region does not prove infeasibility
+    let (out, _) = run(&["-a", "ew", "--bound-factor", "0.5"], "negative-solution.ilp");
+    assert!(out.contains("the ILP might have a solution!"), "{}", out);
+    assert!(!out.contains("instance is infeasible"));
+}
+
+#[test]
+fn timings_json() {
+    let (out, err) = run(&["--timings-json"], "3x3.ilp");
+    assert!(!out.contains("\"phase\""));
+
+    let phases:Vec<String> = err.lines()
+        .map(|line| {
+            let timing:serde_json::Value = serde_json::from_str(line).expect("invalid JSON");
+            assert!(timing["ms"].as_f64().unwrap() >= 0.0);
+            timing["phase"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(phases, ["parse", "preprocess", "details", "solve"]);
+
+    let (_, err) = run(&[], "3x3.ilp");
+    assert!(err.is_empty());
+}
+
+#[test]
+fn sense_overrides_the_file() {
+    // minimize 2x + 3y subject to x + y = 4
+    assert_eq!(objective_value(&run(&[], "minimize.ilp").0), "8");
+    assert_eq!(objective_value(&run(&["--sense", "min"], "minimize.ilp").0), "8");
+    assert_eq!(objective_value(&run(&["--sense", "max"], "minimize.ilp").0), "12");
+
+    assert_eq!(objective_value(&run(&["--sense", "max"], "knapsack-unbounded.ilp").0), "70");
+    assert_eq!(objective_value(&run(&["--sense", "min"], "knapsack-unbounded.ilp").0), "0");
+}
+
+#[test]
+fn combined_pairs_are_counted() {
+    for file in &["3x3.ilp", "single-row.ilp"] {
+        let (out, _) = run(&["-a", "jr"], file);
+        let counts:Vec<usize> = out.lines()
+            .find_map(|line| line.strip_prefix(" -> Combined pairs: "))
+            .expect("the pairs are not reported")
+            .split(|c:char| !c.is_ascii_digit())
+            .filter(|s| !s.is_empty())
+            .take(2)
+            .map(|s| s.parse().unwrap())
+            .collect();
+
+        assert!(counts[1] > 0 && counts[1] <= counts[0], "{}: {:?}", file, counts);
+    }
+}
+
+#[test]
+fn dump_costs() {
+    // max 3x subject to x = 2: the graph has the nodes 0, 1 and 2
+    let (out, _) = run(&["-a", "ew", "--dump-costs"], "single-variable.ilp");
+    let dump:Vec<&str> = out.lines()
+        .skip_while(|line| *line != " -> Node costs:")
+        .skip(1)
+        .take_while(|line| line.starts_with("    "))
+        .collect();
+
+    assert_eq!(dump, ["    [0]: 0", "    [1]: 3", "    [2]: 6"]);
+
+    let (out, _) = run(&["-a", "ew"], "single-variable.ilp");
+    assert!(!out.contains("Node costs"));
+}
+
+#[test]
+fn assignment_format() {
+    let (out, err) = run(&["--format", "assign"], "3x3.ilp");
+    assert_eq!(out, "x1=5\nx2=3\nx3=5\n");
+    assert!(err.contains("Reading file"));
+
+    let (out, _) = run(&["--format", "assign"], "duplicate-columns.ilp");
+    assert_eq!(out, "x=2\ny=2\nz=0\nw=0\n");
+}
+
+#[test]
+fn solver_directive_selects_the_algorithm() {
+    let (out, _) = run(&[], "solver-directive.ilp");
+    assert!(out.contains("Solving ILP with the Jansen & Rohwedder algorithm"), "{}", out);
+
+    // -a overrides the file
+    let (out, _) = run(&["-a", "ew"], "solver-directive.ilp");
+    assert!(out.contains("Solving ILP with the Eisenbrand & Weismantel algorithm"), "{}", out);
+    assert!(!out.contains("Jansen & Rohwedder"));
+}
+
+#[test]
+fn dry_run_reports_the_lp_bound() {
+    // item 1 has the best profit per weight: 11 * 13/2
+    let (out, _) = run(&["--dry-run"], "knapsack-unbounded.ilp");
+    assert!(out.contains("LP relaxation optimum: 71.5 (integer optimum <= 71)"), "{}", out);
+    assert!(!out.contains("objective value"));
+
+    let (out, _) = run(&["--dry-run"], "minimize.ilp");
+    assert!(out.contains("LP relaxation optimum: 8 (integer optimum >= 8)"), "{}", out);
+}
+
+#[test]
+fn standard_form_is_solved_as_given() {
+    // without preprocessing the dominated column x3 stays in the model
+    let (out, _) = run(&["--standard-form"], "single-row.ilp");
+    assert!(!out.contains("Preprocessing"), "{}", out);
+    assert!(out.contains("-> variables:   3\n    [\"x1\", \"x2\", \"x3\"]"), "{}", out);
+    assert!(out.contains(" x1 = 5\n x2 = 0\n x3 = 0\n"), "{}", out);
+    assert_eq!(objective_value(&out), "15");
+
+    // inequalities would need slack variables
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/infeasible.ilp");
+    let output = Command::new(env!("CARGO_BIN_EXE_intopt"))
+        .arg("--standard-form")
+        .arg(path)
+        .output()
+        .expect("failed to run intopt");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("not in standard form"));
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::Path;
+
+use intopt::ilp::{parser, steinitz, discrepancy, PreprocessOptions, SolverOptions};
+use intopt::{ILP, ILPError, Vector};
+
+/*
+    Solves every .ilp file in tests/corpus with both algorithms and compares the
+    result with the sibling .expected file, which contains one of
+    `optimal <objective value>`, `infeasible` or `unbounded`.
+*/
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Optimal(f64),
+    Infeasible,
+    Unbounded
+}
+
+fn expected_outcome(path:&Path) -> Outcome {
+    let text = fs::read_to_string(path.with_extension("expected"))
+        .unwrap_or_else(|e| panic!("{}: missing .expected file ({})", path.display(), e));
+    let words:Vec<&str> = text.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["optimal", value] => Outcome::Optimal(value.parse().expect("invalid objective value")),
+        ["infeasible"] => Outcome::Infeasible,
+        ["unbounded"] => Outcome::Unbounded,
+        _ => panic!("{}: invalid .expected file {:?}", path.display(), text)
+    }
+}
+
+/// Preprocesses like the command line interface and solves with `solve`.
+fn outcome<F>(ilp:&ILP, solve:F) -> Outcome
+    where F: Fn(&ILP) -> Result<Vector, ILPError>
+{
+    let ilp = match ilp.clone().preprocess(PreprocessOptions::default()) {
+        Ok(ilp) => ilp,
+        Err(ILPError::NoSolution) => return Outcome::Infeasible,
+        Err(e) => panic!("preprocessing failed: {}", e)
+    };
+
+    match solve(&ilp) {
+        Ok(x) => {
+            for (i, &b) in ilp.b.iter().enumerate() {
+                assert_eq!(ilp.A.row(i).dot(&x), b, "the solution violates row {}", i + 1);
+            }
+            assert!(x.iter().all(|&v| v >= 0), "the solution has negative entries");
+            Outcome::Optimal(ilp.objective_value(&x))
+        },
+        Err(ILPError::NoSolution) => Outcome::Infeasible,
+        Err(ILPError::Unbounded) => Outcome::Unbounded,
+        Err(e) => panic!("the solver failed: {}", e)
+    }
+}
+
+#[test]
+fn corpus() {
+    let mut files:Vec<_> = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus"))
+        .expect("tests/corpus is missing")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("ilp"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "the corpus is empty");
+
+    let options = SolverOptions::default();
+    for path in files {
+        let expected = expected_outcome(&path);
+        let ilp = parser::parse_file(path.to_str().unwrap())
+            .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+        let ew = outcome(&ilp, |ilp| steinitz::solve(ilp, &options, &mut |_| {}));
+        assert_eq!(ew, expected, "{}: ew", path.display());
+
+        let jr = outcome(&ilp, |ilp| discrepancy::solve(ilp, &options, &mut |_| {}));
+        assert_eq!(jr, expected, "{}: jr", path.display());
+    }
+}
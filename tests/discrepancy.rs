@@ -0,0 +1,128 @@
+use intopt::ilp::{parser, discrepancy, SolverOptions, ProgressEvent};
+use intopt::{ILP, ILPError};
+use std::time::{Duration, Instant};
+
+fn parse(source:&str) -> ILP {
+    parser::parse_str(source).unwrap()
+}
+
+#[test]
+fn scaled_targets_match_float_rounding() {
+    let b = [268_435_453, -134_217_731, 3, -3];
+    let ilp = parse(&format!(
+        "maximize:\n    x1 + x2 + x3 + x4\nsubject to:\n    x1 = {}\n    x2 = {}\n    x3 = {}\n    x4 = {}\n",
+        b[0], b[1], b[2], b[3]
+    ));
+    let targets = discrepancy::scaled_targets(&ilp);
+    let k = discrepancy::compute_K(&ilp);
+
+    // every target is round(b * 2^{i-K}) for some i, in increasing order of i
+    let mut i = 1;
+    for target in targets.iter() {
+        let expected = |i:usize| -> Vec<i32> {
+            b.iter().map(|&v| (v as f64 * 0.5f64.powi((k - i) as i32)).round() as i32).collect()
+        };
+        while i <= k && expected(i) != target.iter().copied().collect::<Vec<_>>() {
+            i += 1;
+        }
+        assert!(i <= k, "{:?} is not a scaled b", target);
+    }
+    assert_eq!(targets.last().unwrap(), &ilp.b);
+}
+
+#[test]
+fn time_limit_interrupts_the_table_construction() {
+    // the first target takes several seconds, single iterations take
+    // longer than a second after a few steps
+    let ilp = parse(
+        "maximize:\n    x1 + 2*x2 + x3 + 3*x4 + x5 + x6\nsubject to:\n    \
+        3*x1 + -2*x2 + x3 + 2*x4 + -x5 + x6 = 40\n    \
+        x1 + 3*x2 + -2*x3 + x4 + 2*x5 + -x6 = 37\n    \
+        -x1 + x2 + 3*x3 + -2*x4 + x5 + 2*x6 = 35\n"
+    );
+    let options = SolverOptions { timeout: Some(Duration::from_secs(1)), ..SolverOptions::default() };
+    let mut targets = 0;
+
+    let start = Instant::now();
+    let result = discrepancy::solve(&ilp, &options, &mut |event| if let ProgressEvent::TableSize { .. } = event {
+        targets += 1;
+    });
+
+    assert!(matches!(result, Err(ILPError::Timeout)));
+    assert_eq!(targets, 1);
+    assert!(start.elapsed() < Duration::from_millis(1500), "{:?}", start.elapsed());
+}
+
+#[test]
+fn table_size_estimate_is_monotonic() {
+    let one_row = parse("maximize:\n    x + y + z\nsubject to:\n    x + y + z = 12\n");
+    let two_rows = parse("maximize:\n    x + y + z\nsubject to:\n    x + y + z = 12\n    x + -y = 0\n");
+    let three_rows = parse("maximize:\n    x + y + z\nsubject to:\n    x + y + z = 12\n    x + -y = 0\n    y + -z = 0\n");
+    assert!(discrepancy::estimate_table_size(&one_row) < discrepancy::estimate_table_size(&two_rows));
+    assert!(discrepancy::estimate_table_size(&two_rows) < discrepancy::estimate_table_size(&three_rows));
+
+    // the same b with larger coefficients, i.e. a larger H
+    let mut previous = 0;
+    for delta in 1..5 {
+        let ilp = parse(&format!("maximize:\n    x + y + z\nsubject to:\n    x + {}*y + z = 12\n", delta));
+        let estimate = discrepancy::estimate_table_size(&ilp);
+        assert!(estimate > previous, "H = {}: {} <= {}", ilp.A.herdisc_upper_bound(), estimate, previous);
+        previous = estimate;
+    }
+}
+
+#[test]
+fn max_var_value_caps_the_solutions() {
+    let ilp = parse("maximize:\n    x + y\nsubject to:\n    x + 2*y = 10\n");
+    let solve = |cap| discrepancy::solve(&ilp, &SolverOptions { max_var_value: cap, ..SolverOptions::default() }, &mut |_| {});
+
+    assert_eq!(solve(None).unwrap().dot(&ilp.c), 10);
+    assert_eq!(solve(Some(50)).unwrap().dot(&ilp.c), 10);
+    assert_eq!(solve(Some(10)).unwrap().dot(&ilp.c), 10);
+    // every solution has x + y >= 5
+    assert!(matches!(solve(Some(4)), Err(ILPError::NoSolution)));
+}
+
+#[test]
+fn incumbents_are_reported_early() {
+    let ilp = parse("maximize:\n    x + 3*y + z\nsubject to:\n    x + 2*y + -z = 6\n    x + -y + z = 3\n");
+    let mut targets = 0;
+    let mut incumbents = Vec::new(); // (cost, number of targets started before)
+
+    let x = discrepancy::solve(&ilp, &SolverOptions::default(), &mut |event| match event {
+        ProgressEvent::TableSize { .. } => targets += 1,
+        ProgressEvent::Incumbent { cost } => incumbents.push((cost, targets)),
+        _ => {}
+    }).unwrap();
+
+    // b is reached for the first target already, long before the table is complete
+    assert_eq!(incumbents.first().map(|&(_, target)| target), Some(1));
+    assert!(targets > 1);
+    // later solutions are better and the last one is returned
+    assert!(incumbents.windows(2).all(|w| w[0].0 < w[1].0), "{:?}", incumbents);
+    assert_eq!(incumbents.last().unwrap().0, x.dot(&ilp.c));
+}
+
+#[test]
+fn scaled_targets_double_towards_b() {
+    let ilp = parse("maximize:\n    x + y + z\nsubject to:\n    x + y = 1000\n    x + -z = -700\n    y + z = 3\n");
+    let targets = discrepancy::scaled_targets(&ilp);
+
+    assert!(targets.first().unwrap().inf_norm() <= 1, "{:?}", targets.first());
+    assert_eq!(targets.last().unwrap(), &ilp.b);
+    // every target is about twice the previous one (entries are rounded)
+    for pair in targets.windows(2) {
+        assert!(pair[1].linf_distance(&pair[0].scale(2)) <= 1, "{:?} -> {:?}", pair[0], pair[1]);
+    }
+}
+
+#[test]
+fn time_limit_falls_back_to_the_heuristic() {
+    let ilp = parse("maximize:\n    x1 + x2 + 3*x3\nsubject to:\n    x1 + x3 = 4000\n    x2 + x3 = 3000\n");
+    let options = SolverOptions { timeout: Some(Duration::from_secs(0)), ..SolverOptions::default() };
+
+    let x = discrepancy::solve(&ilp, &options, &mut |_| {}).unwrap();
+    for (i, &b) in ilp.b.iter().enumerate() {
+        assert_eq!(ilp.A.row(i).dot(&x), b);
+    }
+}
@@ -0,0 +1,38 @@
+use intopt::ilp::parser::{self, ParseError};
+use intopt::ILPError;
+use std::error::Error;
+
+#[test]
+fn ilp_error_messages() {
+    assert_eq!(ILPError::NoSolution.to_string(), "the ILP has no solution");
+    assert_eq!(ILPError::Unbounded.to_string(), "the ILP is unbounded");
+    assert_eq!(ILPError::Timeout.to_string(), "time limit reached, no solution found");
+    assert_eq!(ILPError::ResourceExhausted.to_string(), "memory limit reached, no solution found");
+    assert_eq!(ILPError::Overflow.to_string(), "integer overflow, the result is unknown");
+    assert_eq!(ILPError::UnknownVariable("x7".to_string()).to_string(), "unknown variable x7");
+    assert_eq!(ILPError::InvalidModel("A has no rows or no columns").to_string(), "invalid ILP: A has no rows or no columns");
+    assert_eq!(format!("{:?}", ILPError::NoSolution), "NoSolution");
+}
+
+#[test]
+fn parse_error_messages() {
+    let error = parser::parse_str("maximize:\n    x\nsubject to:\n    x <= 2\n    1 = 2\n").err().unwrap();
+    assert_eq!(error.to_string(), "The constraint '1 = 2' in line 5 is false, the ILP has no solution.");
+    assert!(error.source().is_none());
+
+    let error = parser::parse_file("/nonexistent/model.ilp").err().unwrap();
+    assert!(matches!(error, ParseError::Io(_)));
+    assert!(error.to_string().starts_with("Cannot read file: "));
+    assert!(error.source().is_some());
+}
+
+#[test]
+fn errors_can_be_propagated() {
+    fn solve(source:&str) -> Result<(), Box<dyn Error>> {
+        parser::parse_str(source)?;
+        Err(ILPError::NoSolution)?
+    }
+
+    assert_eq!(solve("maximize:\n    x\nsubject to:\n").unwrap_err().to_string(), "Invalid ILP: there are no constraints.");
+    assert_eq!(solve("maximize:\n    x\nsubject to:\n    x <= 2\n").unwrap_err().to_string(), "the ILP has no solution");
+}
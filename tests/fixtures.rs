@@ -0,0 +1,30 @@
+#![cfg(feature = "fixtures")]
+
+use intopt::ilp::{fixtures, steinitz, discrepancy, SolverOptions};
+use intopt::{ILP, ILPError, Vector};
+
+fn ew(ilp:&ILP) -> Result<Vector, ILPError> {
+    steinitz::solve(ilp, &SolverOptions::default(), &mut |_| {})
+}
+
+fn jr(ilp:&ILP) -> Result<Vector, ILPError> {
+    discrepancy::solve(ilp, &SolverOptions::default(), &mut |_| {})
+}
+
+#[test]
+fn fixtures_have_the_documented_results() {
+    let knapsack = fixtures::knapsack();
+    assert_eq!(ew(&knapsack).unwrap().dot(&knapsack.c), 11);
+    assert_eq!(jr(&knapsack).unwrap().dot(&knapsack.c), 11);
+
+    assert!(matches!(ew(&fixtures::infeasible()), Err(ILPError::NoSolution)));
+    assert!(matches!(jr(&fixtures::infeasible()), Err(ILPError::NoSolution)));
+
+    // (jr takes several seconds in debug builds)
+    assert!(matches!(ew(&fixtures::unbounded()), Err(ILPError::Unbounded)));
+
+    let blocks = fixtures::blocks();
+    assert_eq!(ew(&blocks).unwrap(), Vector::from_slice(&[0, 3, 2, 0]));
+    assert_eq!(jr(&blocks).unwrap(), Vector::from_slice(&[0, 3, 2, 0]));
+    assert_eq!(blocks.variable_names(), ["x1", "x2", "x3", "x4"]);
+}
@@ -0,0 +1,49 @@
+use intopt::ilp::{heuristics, parser, steinitz, SolverOptions};
+use intopt::ILP;
+
+fn knapsack() -> ILP {
+    let source = "maximize:\n    13*x1 + 42*x2 + 10*x3 + 5*x4 + 6*x5 + 23*x6 + 2*x7\nsubject to:\n    2*x1 + 7*x2 + 3*x3 + x4 + 2*x5 + 4*x6 + 2*x7 <= 11\n";
+    parser::parse_str(source).unwrap()
+}
+
+#[test]
+fn more_restarts_are_never_worse() {
+    let ilp = knapsack();
+    let mut last = None;
+
+    for restarts in 0..20 {
+        let objective = heuristics::greedy_restarts(&ilp, restarts).map(|x| {
+            for (i, &b) in ilp.b.iter().enumerate() {
+                assert_eq!(ilp.A.row(i).dot(&x), b);
+            }
+            ilp.objective_value(&x)
+        });
+
+        if let Some(last) = last {
+            let objective = objective.expect("a restart lost the solution");
+            assert!(objective >= last, "{} restarts: {} < {}", restarts, objective, last);
+        }
+        last = objective.or(last);
+    }
+
+    // the plain greedy run gets stuck, the randomized restarts do not
+    assert!(heuristics::greedy_solve(&ilp).is_none());
+    assert!(last.is_some());
+}
+
+#[test]
+fn greedy_is_at_most_optimal() {
+    // greedy takes the expensive column y twice, the optimum is x = 4
+    let source = "maximize:\n    3*x + 5*y\nsubject to:\n    x + 2*y = 4\n";
+    let ilp = parser::parse_str(source).unwrap();
+
+    let x = heuristics::greedy_solve(&ilp).expect("greedy does not reach b");
+    for (i, &b) in ilp.b.iter().enumerate() {
+        assert_eq!(ilp.A.row(i).dot(&x), b);
+    }
+    assert!(x.iter().all(|&v| v >= 0));
+
+    let optimum = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(ilp.objective_value(&x), 10.0);
+    assert_eq!(ilp.objective_value(&optimum), 12.0);
+}
@@ -0,0 +1,53 @@
+use intopt::{Diagnostic, ILP, Matrix, Vector};
+
+#[test]
+fn rank() {
+    // columns (2,1,0), (1,3,1), (0,1,4)
+    let full = Matrix::from_slice(3, 3, &[2, 1, 0, 1, 3, 1, 0, 1, 4]);
+    assert_eq!(full.rank(), Some(3));
+
+    // the third row is the sum of the first two
+    let deficient = Matrix::from_slice(3, 4, &[1, 2, 3, 0, 1, 1, 4, -1, 3, 2, 2, 4]);
+    assert_eq!(deficient.rank(), Some(2));
+
+    assert_eq!(Matrix::zero(2, 3).rank(), Some(0));
+    // more columns than rows
+    assert_eq!(Matrix::from_slice(2, 3, &[1, 0, 0, 1, 5, 7]).rank(), Some(2));
+}
+
+#[test]
+fn rank_overflow() {
+    // the minors of this matrix do not fit into an i128
+    let large = Matrix::from_slice(4, 4, &[
+        2_000_000_011, -1_999_999_973, 1_876_543_211, -2_100_000_001,
+        -1_234_567_891, 2_111_111_113, -1_987_654_323, 1_500_000_007,
+        1_732_050_807, -1_414_213_562, 2_036_067_977, -1_618_033_988,
+        -2_147_483_647, 1_000_000_007, -1_300_000_033, 2_071_723_413
+    ]);
+    assert_eq!(large.rank(), None);
+
+    // the model check skips the rank diagnostic
+    let ilp = ILP::new(large, Vector::from_slice(&[1, 2, 3, 4]), Vector::zero(4)).unwrap();
+    assert!(ilp.check().contains(&Diagnostic::info(
+        "the rank check was skipped, intermediate values are too large.".to_string()
+    )));
+}
+
+#[test]
+fn density() {
+    // columns (1,0), (0,0), (2,-3)
+    let a = Matrix::from_slice(2, 3, &[1, 0, 0, 0, 2, -3]);
+
+    assert_eq!(a.density(), 0.5);
+    assert_eq!(Matrix::zero(2, 2).density(), 0.0);
+}
+
+#[test]
+fn opposite_column_pairs() {
+    // columns (1,-1), (2,0), (-1,1), (0,0), (0,0), (-2,1), (1,-1)
+    let a = Matrix::from_slice(2, 7, &[1, -1, 2, 0, -1, 1, 0, 0, 0, 0, -2, 1, 1, -1]);
+
+    // zero columns are not opposite to each other
+    assert_eq!(a.opposite_column_pairs(), [(0, 2), (2, 6)]);
+    assert!(Matrix::from_slice(2, 2, &[1, 2, 2, 1]).opposite_column_pairs().is_empty());
+}
@@ -0,0 +1,433 @@
+use intopt::ilp::{parser, steinitz, PreprocessOptions, SolverOptions};
+use intopt::{ILP, ILPError, Matrix, Vector, Diagnostic};
+
+#[test]
+fn new_rejects_mismatched_dimensions() {
+    let a = || Matrix::from_slice(1, 2, &[1, 2]);
+
+    assert!(ILP::new(a(), Vector::from_slice(&[3]), Vector::from_slice(&[1, 1])).is_ok());
+    assert!(matches!(
+        ILP::new(a(), Vector::from_slice(&[3, 4]), Vector::from_slice(&[1, 1])),
+        Err(ILPError::InvalidModel(_))
+    ));
+    assert!(matches!(
+        ILP::new(a(), Vector::from_slice(&[3]), Vector::from_slice(&[1])),
+        Err(ILPError::InvalidModel(_))
+    ));
+    assert!(matches!(
+        ILP::new(Matrix::zero(0, 0), Vector::zero(0), Vector::zero(0)),
+        Err(ILPError::InvalidModel(_))
+    ));
+}
+
+#[test]
+fn recommendation_depends_on_the_size_of_b() {
+    let small = parser::parse_str("maximize:\n    x + y + z\nsubject to:\n    x + 2*y + -z = 1\n    x + -y + z = 2\n").unwrap();
+    let large = parser::parse_str("maximize:\n    x + y + z\nsubject to:\n    x + 2*y + -z = 100000\n    x + -y + z = 200000\n").unwrap();
+    let knapsack = parser::parse_str("maximize:\n    3*x + 5*y\nsubject to:\n    2*x + 3*y <= 7\n").unwrap();
+
+    let (algorithm, reason) = small.recommend_algorithm();
+    assert_eq!(algorithm, "ew");
+    assert!(reason.starts_with("estimated graph size"), "{}", reason);
+
+    let (algorithm, reason) = large.recommend_algorithm();
+    assert_eq!(algorithm, "jr");
+    assert!(reason.contains("estimated table size"), "{}", reason);
+
+    assert_eq!(knapsack.recommend_algorithm().0, "kp");
+}
+
+#[test]
+fn objective_upper_bound_is_valid() {
+    let ilp = parser::parse_str("maximize:\n    5*x + 4*y + 3*z\nsubject to:\n    4*x + 3*y + 2*z <= 10\n").unwrap();
+    let optimum = 15; // z = 5
+
+    let bound = ilp.objective_upper_bound().expect("the row has only non-negative entries");
+    assert!(bound >= optimum, "{} < {}", bound, optimum);
+
+    // x - y = 1 does not bound x + y
+    let unbounded = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + -y = 1\n").unwrap();
+    assert_eq!(unbounded.objective_upper_bound(), None);
+}
+
+#[test]
+fn structural_equality() {
+    let source = "maximize:\n    x + 2*y\nsubject to:\n    x + y <= 4\n    x + -y = 1\n";
+    let ilp = parser::parse_str(source).unwrap();
+
+    assert!(parser::parse_str(&ilp.to_ilp_string()).unwrap() == ilp);
+    assert!(parser::parse_str("maximize:\n    x + 2*y\nsubject to:\n    x + y <= 4\n    x + -y = 2\n").unwrap() != ilp);
+    assert!(parser::parse_str("maximize:\n    x + 3*y\nsubject to:\n    x + y <= 4\n    x + -y = 1\n").unwrap() != ilp);
+
+    // the order of the variable names does not matter
+    let named = |vars:&[(&str, usize)]| ILP::with_named_vars(
+        Matrix::from_slice(1, 2, &[1, 2]),
+        Vector::from_slice(&[3]),
+        Vector::from_slice(&[1, 1]),
+        vars.iter().map(|&(name, j)| (name.to_string(), j)).collect()
+    ).unwrap();
+    assert!(named(&[("x", 0), ("y", 1)]) == named(&[("y", 1), ("x", 0)]));
+    assert!(named(&[("x", 0), ("y", 1)]) != named(&[("y", 0), ("x", 1)]));
+}
+
+#[test]
+fn coefficient_histogram() {
+    let ilp = ILP::new(
+        Matrix::from_slice(2, 3, &[1, 0, -3, -1, 1, 0]),
+        Vector::from_slice(&[1, 2]),
+        Vector::from_slice(&[1, 1, 1])
+    ).unwrap();
+
+    // the absolute values are counted: two 0s, three 1s (one of them -1) and a 3
+    assert_eq!(ilp.coefficient_histogram(), vec![2, 3, 0, 1]);
+}
+
+#[test]
+fn preprocessing_pipeline() {
+    // row 1 is row 2 times 2, w has a zero column and y is a copy of z with a lower cost
+    let source = "maximize:\n    x + y + 2*z + -w\nsubject to:\n    -2*x + 2*y + 2*z + 0*w = 4\n    -x + y + z = 2\n";
+    let ilp = parser::parse_str(source).unwrap().preprocess(PreprocessOptions::default()).unwrap();
+
+    let messages:Vec<String> = ilp.diagnostics().iter().map(|d| d.to_string()).collect();
+    assert!(messages.iter().any(|m| m.contains("duplicate row")), "{:?}", messages);
+    assert!(messages.iter().any(|m| m.contains("zero column")), "{:?}", messages);
+    assert!(messages.iter().any(|m| m.contains("dominated column")), "{:?}", messages);
+
+    assert_eq!(ilp.A.num_cols(), 2);
+    assert_eq!(ilp.A.row(0), Vector::from_slice(&[-1, 1]));
+    assert_eq!(ilp.b, Vector::from_slice(&[2]));
+
+    // an infeasible row is detected by the gcd pass
+    let infeasible = parser::parse_str("maximize:\n    x + y\nsubject to:\n    2*x + 4*y = 3\n").unwrap();
+    assert!(matches!(infeasible.preprocess(PreprocessOptions::default()), Err(ILPError::NoSolution)));
+}
+
+#[test]
+fn fixed_variables_satisfy_the_original() {
+    let original = parser::parse_str("maximize:\n    x + 2*y + 3*z\nsubject to:\n    x + y + z = 6\n    x + -y + 2*z <= 5\n").unwrap();
+    let fixed = original.clone().fix_variable("y", 2).unwrap();
+    assert_eq!(fixed.variable_names(), vec!["x", "z"]);
+
+    let reduced = steinitz::solve(&fixed, &SolverOptions::default(), &mut |_| {}).unwrap();
+    let x = fixed.map_solution_back(&reduced);
+
+    assert_eq!(x.iter().nth(1), Some(&2));
+    for (i, &b) in original.b.iter().enumerate() {
+        assert_eq!(original.A.row(i).dot(&x), b);
+    }
+    // the objective includes the cost of y
+    assert_eq!(fixed.objective_value(&reduced), original.objective_value(&x));
+
+    assert!(matches!(original.clone().fix_variable("y", 7), Err(ILPError::NoSolution)));
+    assert!(matches!(original.fix_variable("w", 1), Err(ILPError::UnknownVariable(_))));
+}
+
+#[test]
+fn independent_blocks() {
+    let ilp = parser::parse_str("maximize:\n    x1 + 2*x2 + 3*x3 + x4\nsubject to:\n    x1 + x2 = 3\n    2*x3 + x4 = 4\n").unwrap();
+    let options = SolverOptions::default();
+
+    assert_eq!(ilp.blocks().len(), 2);
+    assert_eq!(ilp.decompose().len(), 2);
+
+    let direct = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+    let combined = ilp.solve_decomposed(|block| steinitz::solve(block, &options, &mut |_| {})).unwrap();
+    assert_eq!(combined, direct);
+    assert_eq!(combined, Vector::from_slice(&[0, 3, 2, 0]));
+
+    // the first block is unbounded, but the second one has no solution
+    let infeasible = parser::parse_str("maximize:\n    x1 + x2 + x3 + x4\nsubject to:\n    x1 + -x2 = 1\n    2*x3 + 2*x4 = 3\n").unwrap();
+    let result = infeasible.solve_decomposed(|block| steinitz::solve(block, &options, &mut |_| {}));
+    assert!(matches!(result, Err(ILPError::NoSolution)));
+}
+
+#[test]
+fn redundant_constraints_are_reported() {
+    // the third row is the sum of the first two
+    let ilp = parser::parse_str("maximize:\n    2*x + y + 3*z\nsubject to:\n    x + y + z = 4\n    2*x + y = 3\n    3*x + 2*y + z = 7\n").unwrap();
+
+    assert!(ilp.check().contains(&Diagnostic::warning("A has rank 2 < 3 constraints; 1 may be redundant.".to_string())));
+    assert_eq!(ilp.check().len(), 1);
+    let x = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(x.dot(&ilp.c), 9);
+
+    let contradicting = parser::parse_str("maximize:\n    2*x + y + 3*z\nsubject to:\n    x + y + z = 4\n    2*x + y = 3\n    3*x + 2*y + z = 8\n").unwrap();
+    assert!(contradicting.check().contains(&Diagnostic::warning(
+        "the dependent constraints are contradicting, Ax=b has no solution.".to_string()
+    )));
+}
+
+#[test]
+fn deltas_follow_the_row_gcd_reduction() {
+    let ilp = parser::parse_str("maximize:\n    x + y\nsubject to:\n    6*x + 4*y = 20\n    3*x + 9*y = 30\n").unwrap();
+    assert_eq!((ilp.delta_A(), ilp.delta_b()), (9, 30));
+
+    let reduced = ilp.reduce_row_gcd().unwrap();
+    assert_eq!(reduced.b, Vector::from_slice(&[10, 10]));
+    assert_eq!((reduced.delta_A(), reduced.delta_b()), (3, 10));
+
+    let odd = parser::parse_str("maximize:\n    x + y\nsubject to:\n    2*x + 4*y = 5\n").unwrap();
+    assert!(matches!(odd.reduce_row_gcd(), Err(ILPError::NoSolution)));
+}
+
+#[test]
+fn solution_support_pairs() {
+    let ilp = parser::parse_str("maximize:\n    x + y + z\nsubject to:\n    x + 2*y + 3*z <= 10\n").unwrap();
+    let x = Vector::from_slice(&[3, 0, 2, 1]);
+
+    assert_eq!(ilp.solution_support(&x), [(0, 3), (2, 2), (3, 1)]);
+    assert!(ilp.solution_support(&Vector::zero(4)).is_empty());
+}
+
+#[test]
+fn integer_solution_ignoring_signs() {
+    let single = |b| ILP::new(Matrix::from_slice(1, 1, &[2]), Vector::from_slice(&[b]), Vector::from_slice(&[1])).unwrap();
+    assert_eq!(single(3).integer_feasible_ignoring_signs().unwrap(), None);
+    assert_eq!(single(4).integer_feasible_ignoring_signs().unwrap(), Some(Vector::from_slice(&[2])));
+
+    // x1 + x2 = 3, x1 - x2 = 5 only has the solution (4, -1)
+    let ilp = ILP::new(
+        Matrix::from_slice(2, 2, &[1, 1, 1, -1]), Vector::from_slice(&[3, 5]), Vector::from_slice(&[1, 1])
+    ).unwrap();
+    assert_eq!(ilp.integer_feasible_ignoring_signs().unwrap(), Some(Vector::from_slice(&[4, -1])));
+
+    // more columns than rows: any solution will do
+    let ilp = ILP::new(
+        Matrix::from_slice(2, 3, &[3, 1, 5, 2, 4, -1]), Vector::from_slice(&[7, 2]), Vector::from_slice(&[1, 1, 1])
+    ).unwrap();
+    let x = ilp.integer_feasible_ignoring_signs().unwrap().unwrap();
+    assert_eq!((ilp.A.row(0).dot(&x), ilp.A.row(1).dot(&x)), (7, 2));
+}
+
+#[test]
+fn constraint_slacks() {
+    let ilp = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + 2*y <= 10\n    3*x + y <= 9\n    x + -y = 0\n").unwrap();
+    let x = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    let structural:Vec<_> = x.iter().copied().take(2).collect();
+    let (x1, x2) = (structural[0], structural[1]);
+
+    assert_eq!((x1, x2), (2, 2));
+    assert_eq!(ilp.constraint_slacks(&x), [10 - (x1 + 2*x2), 9 - (3*x1 + x2), 0]);
+
+    let ilp = parser::parse_str("minimize:\n    x\nsubject to:\n    x >= 3\n").unwrap();
+    let x = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(ilp.constraint_slacks(&Vector::from_slice(&[5, 2])), [2]);
+    assert_eq!(ilp.constraint_slacks(&x), [0]);
+}
+
+#[test]
+fn content_hash() {
+    let hash = |source:&str| parser::parse_str(source).unwrap().content_hash();
+    let original = hash("maximize:\n    3*x + 2*y\nsubject to:\n    x + y <= 4\n");
+
+    assert_eq!(original, hash("maximize:\n    3*a + 2*b\nsubject to:\n    a + b <= 4\n"));
+    assert_ne!(original, hash("maximize:\n    3*x + 2*y\nsubject to:\n    x + 2*y <= 4\n"));
+    assert_ne!(original, hash("maximize:\n    3*x + 2*y\nsubject to:\n    x + y <= 5\n"));
+    assert_ne!(original, hash("maximize:\n    3*x + y\nsubject to:\n    x + y <= 4\n"));
+    assert_ne!(original, hash("minimize:\n    -3*x + -2*y\nsubject to:\n    x + y <= 4\n"));
+    // stable across runs and platforms
+    assert_eq!(original, 14_580_455_043_635_933_762);
+}
+
+#[test]
+fn dominated_columns_are_removed() {
+    // y is x with a lower cost, w is a duplicate of x, z is no multiple of x
+    let ilp = parser::parse_str("maximize:\n    3*x + 2*y + 5*z + 3*w\nsubject to:\n    x + y + 2*z + w = 6\n    x + y + 3*z + w <= 7\n").unwrap();
+    let reduced = ilp.clone().remove_dominated_columns();
+
+    assert_eq!(reduced.variable_names(), ["x", "z"]);
+    assert_eq!(reduced.c, Vector::from_slice(&[3, 5, 0]));
+    assert!(reduced.diagnostics().contains(&Diagnostic::info("Removed 2 dominated column(s).".to_string())));
+
+    let options = SolverOptions::default();
+    let x = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+    let y = steinitz::solve(&reduced, &options, &mut |_| {}).unwrap();
+    assert_eq!(ilp.objective_value(&x), reduced.objective_value(&y));
+}
+
+#[test]
+fn monoid_feasibility() {
+    let ilp = |rows, columns, a:&[i32], b:&[i32]| ILP::new(
+        Matrix::from_slice(rows, columns, a), Vector::from_slice(b), Vector::from_slice(&vec![1; columns])
+    ).unwrap();
+
+    // 7 is no sum of 3s and 5s, but b is in the lattice (gcd 1)
+    assert_eq!(ilp(1, 2, &[3, 5], &[7]).is_feasible_monoid(), Some(false));
+    assert!(ilp(1, 2, &[3, 5], &[7]).b_in_lattice().unwrap());
+    assert_eq!(ilp(1, 2, &[3, 5], &[8]).is_feasible_monoid(), Some(true));
+
+    // columns (1,2) and (2,1)
+    assert_eq!(ilp(2, 2, &[1, 2, 2, 1], &[3, 3]).is_feasible_monoid(), Some(true));
+    assert_eq!(ilp(2, 2, &[1, 2, 2, 1], &[2, 2]).is_feasible_monoid(), Some(false));
+
+    assert_eq!(ilp(1, 2, &[3, -5], &[7]).is_feasible_monoid(), None);
+}
+
+#[test]
+fn add_variable() {
+    let ilp = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + 2*y <= 6\n").unwrap();
+    let n = ilp.A.num_cols();
+    let ilp = ilp.add_variable("z", Vector::from_slice(&[3]), 4);
+
+    assert_eq!(ilp.A.num_cols(), n + 1);
+    assert_eq!(ilp.A.iter().last(), Some(&Vector::from_slice(&[3])));
+    assert_eq!(ilp.c.iter().last(), Some(&4));
+    assert_eq!(ilp.variable_names(), ["x", "y", "z"]);
+    assert_eq!(ilp.delta_A(), 3);
+
+    // z has the best ratio of cost and weight
+    let x = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(ilp.objective_value(&x), 8.0);
+    assert_eq!(x.iter().last(), Some(&2));
+}
+
+#[test]
+fn add_variable_to_a_minimization() {
+    let source = "minimize:\n    2*x + 3*y\nsubject to:\n    x + y >= 4\n";
+    let mut ilp = parser::parse_str(source).unwrap();
+    ilp.scale_objective(3);
+    // z is cheaper than x and y
+    let ilp = ilp.add_variable("z", Vector::from_slice(&[2]), 3);
+
+    let x = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(x.iter().last(), Some(&2));
+    assert_eq!(ilp.objective_value(&x), 6.0);
+    assert!(ilp.to_ilp_string().starts_with("minimize:\n    2*x + 3*y + 3*z"), "{}", ilp.to_ilp_string());
+}
+
+#[test]
+#[should_panic(expected = "the column has to have an entry for every row")]
+fn add_variable_checks_the_column() {
+    let ilp = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + 2*y <= 6\n").unwrap();
+    ilp.add_variable("z", Vector::from_slice(&[3, 1]), 4);
+}
+
+#[test]
+fn scaled_objective_is_reported_in_model_units() {
+    let source = "minimize:\n    2*x + 3*y + z\nsubject to:\n    x + y + z = 4\n    y + -z >= 1\n";
+    let original = parser::parse_str(source).unwrap();
+    let mut scaled = parser::parse_str(source).unwrap();
+    scaled.scale_objective(4);
+
+    assert_eq!(scaled.objective_scale(), 4);
+    assert_eq!(scaled.c, original.c.scale(4));
+    let options = SolverOptions::default();
+    let x = steinitz::solve(&original, &options, &mut |_| {}).unwrap();
+    let y = steinitz::solve(&scaled, &options, &mut |_| {}).unwrap();
+    assert_eq!(original.objective_value(&x), 9.0);
+    assert_eq!(scaled.objective_value(&y), 9.0);
+    // the model is written with the original coefficients
+    assert!(scaled.to_ilp_string().starts_with("minimize:\n    2*x + 3*y + 1*z"), "{}", scaled.to_ilp_string());
+
+    // with the cost of a fixed variable
+    let fixed = scaled.fix_variable("x", 2).unwrap();
+    let y = steinitz::solve(&fixed, &options, &mut |_| {}).unwrap();
+    assert_eq!(fixed.objective_value(&y), 10.0);
+}
+
+#[test]
+fn column_efficiency() {
+    // columns (2,1), (1,-3), (0,0), (0,0), (1,1)
+    let ilp = ILP::new(
+        Matrix::from_slice(2, 5, &[2, 1, 1, -3, 0, 0, 0, 0, 1, 1]),
+        Vector::from_slice(&[4, 1]),
+        Vector::from_slice(&[6, -2, 1, 0, 0])
+    ).unwrap();
+
+    assert_eq!(ilp.column_efficiency(), [2.0, -0.5, f32::INFINITY, 0.0, 0.0]);
+    let negative = ilp.with_cost(Vector::from_slice(&[0, 0, -1, 0, 0]));
+    assert_eq!(negative.column_efficiency()[2], f32::NEG_INFINITY);
+}
+
+#[test]
+fn with_cost_swaps_the_objective() {
+    let ilp = parser::parse_str("maximize:\n    3*x + 5*y\nsubject to:\n    x + 2*y = 4\n").unwrap();
+    let options = SolverOptions::default();
+
+    let x = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+    assert_eq!(x.iter().copied().collect::<Vec<_>>(), [4, 0]);
+
+    let swapped = ilp.clone().with_cost(Vector::from_slice(&[1, 5]));
+    assert!(swapped.A == ilp.A);
+    assert_eq!(swapped.b, ilp.b);
+    assert_eq!(swapped.delta_A(), ilp.delta_A());
+    assert_eq!(swapped.variable_names(), ilp.variable_names());
+
+    let x = steinitz::solve(&swapped, &options, &mut |_| {}).unwrap();
+    assert_eq!(x.iter().copied().collect::<Vec<_>>(), [0, 2]);
+    assert_eq!(swapped.objective_value(&x), 10.0);
+}
+
+#[test]
+#[should_panic(expected = "c has to have an entry for every column")]
+fn with_cost_checks_the_length() {
+    let ilp = ILP::new(Matrix::from_slice(1, 2, &[1, 2]), Vector::from_slice(&[4]), Vector::from_slice(&[1, 1])).unwrap();
+    ilp.with_cost(Vector::from_slice(&[1]));
+}
+
+#[test]
+fn slack_variables_with_a_cost_are_reported() {
+    let ilp = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\n    x <= 3\n").unwrap();
+    assert_eq!(ilp.A.num_cols(), 4);
+    assert!(ilp.costly_slack_variables().is_empty());
+
+    // x, y and the slack variables of both rows, by accident the first slack gets a cost
+    let ilp = ilp.with_cost(Vector::from_slice(&[1, 1, -2, 0]));
+    assert_eq!(ilp.costly_slack_variables(), [(0, -2)]);
+    assert!(ilp.check().contains(&Diagnostic::warning(
+        "the slack variable of row 1 has cost -2 (instead of 0).".to_string()
+    )));
+}
+
+#[test]
+fn objective_cap_is_enforced() {
+    let options = SolverOptions::default();
+    let ilp = parser::parse_str("maximize:\n    3*x + 2*y\nsubject to:\n    x + y <= 4\n").unwrap();
+    let x = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+    assert_eq!(ilp.objective_value(&x), 12.0);
+
+    for cap in [11, 10, 7] {
+        let capped = ilp.clone().add_objective_cap(cap);
+        let x = steinitz::solve(&capped, &options, &mut |_| {}).unwrap();
+        assert_eq!(capped.objective_value(&x), cap as f64, "cap {}", cap);
+    }
+
+    // minimization: the cap is an upper bound of the minimized objective
+    let ilp = parser::parse_str("minimize:\n    3*x + 2*y\nsubject to:\n    x + y >= 4\n").unwrap();
+    let capped = ilp.add_objective_cap(7);
+    assert!(matches!(steinitz::solve(&capped, &options, &mut |_| {}), Err(ILPError::NoSolution)));
+}
+
+#[test]
+fn solutions_are_mapped_back_through_simplify() {
+    // w duplicates y
+    let original = parser::parse_str("maximize:\n    2*x + 3*y + 3*w\nsubject to:\n    x + y + w = 4\n    x + 2*y + 2*w = 6\n").unwrap();
+    let simplified = original.clone().simplify();
+    assert_eq!(simplified.variable_names(), vec!["x", "y"]);
+
+    let reduced = steinitz::solve(&simplified, &SolverOptions::default(), &mut |_| {}).unwrap();
+    let x = simplified.map_solution_back(&reduced);
+    assert_eq!(x, Vector::from_slice(&[2, 2, 0]));
+    for (i, &b) in original.b.iter().enumerate() {
+        assert_eq!(original.A.row(i).dot(&x), b);
+    }
+    assert_eq!(simplified.objective_value(&reduced), original.objective_value(&x));
+}
+
+#[test]
+fn duplicate_and_zero_columns_are_diagnosed() {
+    // w duplicates y, z is a zero column
+    let ilp = parser::parse_str("maximize:\n    2*x + 3*y + 3*w + -z\nsubject to:\n    x + y + w = 4\n    x + 2*y + 2*w + 0*z = 6\n").unwrap();
+    let zero_column = Diagnostic::warning("z only has zero coefficients in the constraints.".to_string());
+    assert_eq!(ilp.diagnostics(), std::slice::from_ref(&zero_column));
+
+    let simplified = ilp.clone().simplify();
+    assert_eq!(simplified.diagnostics(), [zero_column.clone(), Diagnostic::info("Removed 1 column(s).".to_string())]);
+
+    let preprocessed = ilp.preprocess(PreprocessOptions::default()).unwrap();
+    assert_eq!(preprocessed.diagnostics(), [
+        zero_column,
+        Diagnostic::info("Removed 1 zero column(s).".to_string()),
+        Diagnostic::info("Removed 1 dominated column(s).".to_string())
+    ]);
+}
@@ -0,0 +1,218 @@
+use intopt::ilp::{parser, steinitz, Sense, SolverOptions};
+use intopt::{Diagnostic, ILPError, Matrix, Vector};
+
+#[test]
+fn ilp_string_round_trip() {
+    let source = "minimize:\n    x + 2*y\nsubject to:\n    x + y >= 2\n    x + -y <= 3\n    x + 3*y = 6\n";
+    let ilp = parser::parse_str(source).unwrap();
+    let printed = ilp.to_ilp_string();
+
+    assert!(printed.starts_with("minimize:"), "{}", printed);
+    assert!(printed.contains(">= 2") && printed.contains("<= 3"), "{}", printed);
+
+    let reparsed = parser::parse_str(&printed).unwrap();
+    assert!(reparsed == ilp);
+    assert_eq!(reparsed.sense(), Sense::Minimize);
+    assert_eq!(reparsed.to_ilp_string(), printed);
+}
+
+#[test]
+fn binaries_are_declared_not_inferred() {
+    let source = "maximize:\n    x + y + z\nsubject to:\n    x + y + z <= 5\nbinary x\nbounds:\n    y <= 1\n    z <= 3\n";
+    let ilp = parser::parse_str(source).unwrap();
+
+    assert_eq!(ilp.binaries(), ["x".to_string()]);
+}
+
+#[test]
+fn model_without_constraints_is_degenerate() {
+    let result = parser::parse_str("maximize:\n    x + y\nsubject to:\n");
+
+    assert!(matches!(result, Err(parser::ParseError::Degenerate(_))));
+}
+
+#[test]
+fn scientific_coefficients_are_rejected() {
+    match parser::parse_str("maximize:\n    x + y\nsubject to:\n    1e3*x + y = 5\n") {
+        Err(parser::ParseError::Unsupported { line, token }) => {
+            assert_eq!(line, 4);
+            assert_eq!(token, "1e3");
+        },
+        _ => panic!("expected an unsupported coefficient error")
+    }
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzipped_files_are_decompressed() {
+    use std::io::Write;
+
+    let original = format!("{}/tests/corpus/3x3.ilp", env!("CARGO_MANIFEST_DIR"));
+    let compressed = std::env::temp_dir().join(format!("intopt-{}-3x3.ilp.gz", std::process::id()));
+    let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(&compressed).unwrap(), flate2::Compression::default());
+    encoder.write_all(&std::fs::read(&original).unwrap()).unwrap();
+    encoder.finish().unwrap();
+
+    let decompressed = parser::parse_file(compressed.to_str().unwrap());
+    std::fs::remove_file(&compressed).unwrap();
+
+    assert!(decompressed.unwrap() == parser::parse_file(&original).unwrap());
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn gzipped_files_require_the_feature() {
+    let path = std::env::temp_dir().join(format!("intopt-{}-3x3.ilp.gz", std::process::id()));
+    std::fs::write(&path, b"not read").unwrap();
+
+    let result = parser::parse_file(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    match result {
+        Err(parser::ParseError::Io(e)) => assert!(e.to_string().contains("gzip feature"), "{}", e),
+        _ => panic!("expected an error")
+    }
+}
+
+#[test]
+fn satisfied_constant_constraints_are_dropped() {
+    let with = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\n    3 = 3\n").unwrap();
+    let without = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\n").unwrap();
+
+    assert!(with == without);
+}
+
+#[test]
+fn contradictory_constant_constraints_are_infeasible() {
+    match parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\n    3 = 5\n") {
+        Err(parser::ParseError::Infeasible { line, constraint }) => {
+            assert_eq!(line, 5);
+            assert_eq!(constraint, "3 = 5");
+        },
+        _ => panic!("expected an infeasible constraint error")
+    }
+}
+
+#[test]
+fn bounds_section() {
+    let unbounded = parser::parse_str("maximize:\n    3*x + 2*y\nsubject to:\n    x + y <= 5\n").unwrap();
+    let bounded = parser::parse_str("maximize:\n    3*x + 2*y\nsubject to:\n    x + y <= 5\nbounds:\n    1 <= x <= 2;\n    y <= 4\n").unwrap();
+    // x <= 2, x >= 1 and y <= 4 are encoded as constraints (with a slack variable each)
+    assert_eq!(bounded.A.num_cols(), unbounded.A.num_cols() + 3);
+    assert_eq!(bounded.b.iter().count(), 4);
+
+    let options = SolverOptions::default();
+    let x = steinitz::solve(&unbounded, &options, &mut |_| {}).unwrap();
+    assert_eq!(unbounded.objective_value(&x), 15.0);
+    let x = steinitz::solve(&bounded, &options, &mut |_| {}).unwrap();
+    assert_eq!(bounded.objective_value(&x), 12.0);
+
+    // the lower bound
+    let ilp = parser::parse_str("maximize:\n    y + -x\nsubject to:\n    x + y <= 5\nbounds:\n    1 <= x <= 2\n").unwrap();
+    let x = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+    assert_eq!(ilp.objective_value(&x), 3.0);
+}
+
+#[test]
+fn variable_names_in_column_order() {
+    // the order of the first appearance: objective, constraints, declarations
+    let ilp = parser::parse_str("maximize:\n    b + 2*a\nsubject to:\n    c + a + -b <= 4\n    d + c = 3\nbinary e\n").unwrap();
+
+    assert_eq!(ilp.variable_names(), ["b", "a", "c", "d", "e"]);
+    // the slack variables are not included
+    assert!(ilp.A.num_cols() > 5);
+}
+
+#[test]
+fn range_constraints() {
+    let range = parser::parse_str("maximize:\n    x + y\nsubject to:\n    2 <= x + y <= 5\n").unwrap();
+
+    // -x - y + s1 = -2 and x + y + s2 = 5
+    assert!(range.A == Matrix::from_slice(2, 4, &[-1, 1, -1, 1, 1, 0, 0, 1]), "{}", range.A);
+    assert_eq!(range.b, Vector::from_slice(&[-2, 5]));
+
+    let x = steinitz::solve(&range, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(range.objective_value(&x), 5.0);
+    let min = parser::parse_str("minimize:\n    x + y\nsubject to:\n    2 <= x + y <= 5\n").unwrap();
+    let x = steinitz::solve(&min, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(min.objective_value(&x), 2.0);
+}
+
+#[test]
+fn every_inequality_has_its_own_slack_column() {
+    let ilp = parser::parse_str(
+        "maximize:\n    x + y\nsubject to:\n    x + y <= 4\n    x + -y = 1\n    2*x + y >= 2\n    x + 3*y <= 9\nbounds:\n    y <= 3\n"
+    ).unwrap();
+    let names = ilp.variable_names().len();
+
+    // three inequalities and a bound
+    assert_eq!(ilp.num_slack_variables(), 4);
+    assert_eq!(ilp.A.num_cols(), names + 4);
+
+    // the slack columns are (negative) unit vectors of different rows
+    let mut rows:Vec<usize> = ilp.A.iter().skip(names)
+        .map(|column| {
+            assert_eq!(column.nnz(), 1);
+            column.iter().position(|&a| a != 0).unwrap()
+        })
+        .collect();
+    rows.sort_unstable();
+    rows.dedup();
+    assert_eq!(rows.len(), 4);
+    assert!(!rows.contains(&1), "the equation has a slack variable");
+}
+
+#[test]
+fn solver_directive() {
+    let with = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\nsolver: jr;\n").unwrap();
+    let without = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\n").unwrap();
+
+    assert_eq!(with.algorithm(), Some("jr"));
+    assert_eq!(without.algorithm(), None);
+}
+
+#[test]
+fn objective_keyword_is_strict() {
+    for keyword in &["maximise", "max", "minimise", "optimize"] {
+        let result = parser::parse_str(&format!("{}:\n    x + y\nsubject to:\n    x + y <= 4\n", keyword));
+        assert!(matches!(result, Err(parser::ParseError::Syntax(_))), "{}", keyword);
+    }
+
+    for keyword in &["MAXIMIZE", "Maximize", "minimize", "MiNiMiZe"] {
+        assert!(parser::parse_str(&format!("{}:\n    x + y\nsubject to:\n    x + y <= 4\n", keyword)).is_ok(), "{}", keyword);
+    }
+    let ilp = parser::parse_str("Minimize:\n    x + y\nsubject to:\n    x + y >= 4\n").unwrap();
+    assert!(ilp.sense() == Sense::Minimize);
+}
+
+#[test]
+fn cancelling_constraints() {
+    let ilp = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\n    x + 2*y = 2*y + x\n").unwrap();
+    let without = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\n").unwrap();
+
+    assert!(ilp == without);
+    assert!(ilp.diagnostics().contains(&Diagnostic::info(
+        "Dropped the constraint 'x + 2*y = 2*y + x' in line 5, its variables cancel.".to_string()
+    )));
+
+    match parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + y <= 4\n    x + 2 = x + 3\n") {
+        Err(parser::ParseError::Infeasible { line, .. }) => assert_eq!(line, 5),
+        _ => panic!("expected an infeasible constraint error")
+    }
+}
+
+#[test]
+fn zero_coefficients() {
+    let ilp = parser::parse_str("maximize:\n    y\nsubject to:\n    0*x + y = 3\n").unwrap();
+
+    // x is registered with a zero column
+    assert_eq!(ilp.variable_names(), ["y", "x"]);
+    assert!(ilp.A == Matrix::from_slice(1, 2, &[1, 0]));
+    assert!(ilp.diagnostics().contains(&Diagnostic::warning("x only has zero coefficients in the constraints.".to_string())));
+    let x = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(ilp.objective_value(&x), 3.0);
+
+    // a zero column with a positive cost can be increased without bound
+    let ilp = parser::parse_str("maximize:\n    x + y\nsubject to:\n    0*x + y = 3\n").unwrap();
+    assert!(matches!(steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}), Err(ILPError::Unbounded)));
+}
@@ -0,0 +1,56 @@
+use intopt::ilp::{parser, relaxation, steinitz, SolverOptions};
+use intopt::ILPError;
+
+const EPS: f64 = 1e-6;
+
+#[test]
+fn complementary_slackness() {
+    let ilp = parser::parse_str("maximize:\n    3*x + 2*y\nsubject to:\n    x + y <= 4\n    x + 3*y <= 6\n    x <= 3\n").unwrap();
+    let lp = relaxation::solve(&ilp).unwrap();
+    let duals = relaxation::dual_values(&ilp).unwrap();
+    assert_eq!(duals.len(), lp.duals.len());
+
+    // strong duality
+    let by:f64 = ilp.b.iter().zip(lp.duals.iter()).map(|(&b, &y)| b as f64 * y).sum();
+    assert!((lp.objective - by).abs() < EPS, "{} != {}", lp.objective, by);
+    assert!((lp.objective - 11.0).abs() < EPS);
+
+    for (j, (column, &c)) in ilp.A.iter().zip(ilp.c.iter()).enumerate() {
+        let reduced:f64 = column.iter().zip(lp.duals.iter()).map(|(&a, &y)| a as f64 * y).sum::<f64>() - c as f64;
+        // dual feasibility and x_j > 0 => (A^T y)_j = c_j
+        assert!(reduced > -EPS, "column {}: {}", j, reduced);
+        assert!(lp.x[j] < EPS || reduced.abs() < EPS, "column {}: x = {}, reduced cost {}", j, lp.x[j], reduced);
+    }
+}
+
+#[test]
+fn conic_hull() {
+    let parse = |b:(i32, i32)| parser::parse_str(&format!(
+        "maximize:\n    x + y\nsubject to:\n    x + y = {}\n    x + -y = {}\n", b.0, b.1
+    )).unwrap();
+
+    // x = 4, y = -1
+    let outside = parse((3, 5));
+    assert!(!outside.in_conic_hull());
+    assert!(matches!(steinitz::solve(&outside, &SolverOptions::default(), &mut |_| {}), Err(ILPError::NoSolution)));
+
+    // x = 2, y = 1
+    assert!(parse((3, 1)).in_conic_hull());
+    // x = 1.5, y = 0.5 is a real but no integer solution
+    assert!(parse((2, 1)).in_conic_hull());
+}
+
+#[test]
+fn lattice_width_c() {
+    let ilp = parser::parse_str("maximize:\n    3*x + 2*y\nsubject to:\n    x + y <= 4\n    x + 3*y <= 6\n    x <= 3\n    2*x + y >= 3\n").unwrap();
+    let (min, max) = ilp.lattice_width_c().unwrap();
+    assert_eq!((min, max), (5, 11));
+
+    let x = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    let optimum = x.dot(&ilp.c);
+    assert!(min <= optimum && optimum <= max, "{} not in [{}, {}]", optimum, min, max);
+
+    // unbounded in the direction of c
+    let ray = parser::parse_str("maximize:\n    x + y\nsubject to:\n    x + -y <= 2\n").unwrap();
+    assert_eq!(ray.lattice_width_c(), None);
+}
@@ -0,0 +1,17 @@
+#![cfg(feature = "serde")]
+
+use intopt::ilp::parser;
+use intopt::ILP;
+
+#[test]
+fn ilp_json_round_trip() {
+    let ilp = parser::parse_str("minimize:\n    x + 2*y\nsubject to:\n    x + y >= 2\n    x + -y <= 3\nbinary x\n").unwrap();
+
+    let json = serde_json::to_string(&ilp).unwrap();
+    let deserialized:ILP = serde_json::from_str(&json).unwrap();
+
+    assert!(deserialized == ilp);
+    assert_eq!(deserialized.sense(), ilp.sense());
+    assert_eq!(deserialized.binaries(), ilp.binaries());
+    assert_eq!(deserialized.to_ilp_string(), ilp.to_ilp_string());
+}
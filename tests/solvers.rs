@@ -0,0 +1,179 @@
+use intopt::ilp::{parser, reference, steinitz, discrepancy, knapsack, SolverOptions, ProgressEvent, TieBreak, SPARSE_DENSITY};
+use intopt::{ILP, IntData, Vector};
+
+fn parse(source:&str) -> ILP {
+    parser::parse_str(source).unwrap()
+}
+
+/// The objective values of ew, jr and the brute force solver (in this order).
+fn objectives(ilp:&ILP, var_upper:IntData) -> [f64; 3] {
+    let options = SolverOptions::default();
+    let ew = steinitz::solve(ilp, &options, &mut |_| {}).unwrap();
+    let jr = discrepancy::solve(ilp, &options, &mut |_| {}).unwrap();
+    let reference = reference::brute_force_solve(ilp, var_upper).unwrap();
+
+    [ilp.objective_value(&ew), ilp.objective_value(&jr), ilp.objective_value(&reference)]
+}
+
+#[test]
+fn single_row_subset_sum() {
+    // subset sum with a target that is not reachable with the largest items only
+    let ilp = parse("maximize:\n    3*x1 + 4*x2 + 5*x3 + 9*x4\nsubject to:\n    3*x1 + 4*x2 + 5*x3 + 9*x4 = 13\n");
+
+    assert_eq!(objectives(&ilp, 13), [13.0; 3]);
+}
+
+#[test]
+fn single_row_knapsack() {
+    let ilp = parse("maximize:\n    5*x + 4*y + 3*z\nsubject to:\n    4*x + 3*y + 2*z = 10\n");
+
+    assert_eq!(objectives(&ilp, 10), [15.0; 3]);
+}
+
+#[test]
+fn algorithms_agree_with_brute_force() {
+    let instances = [
+        "maximize:\n    x + y + z\nsubject to:\n    x + 2*y + -z = 1\n    x + -y + z = 2\n",
+        "maximize:\n    2*x + 3*y + z\nsubject to:\n    x + y + z = 4\n    x + 2*y <= 5\n",
+        "minimize:\n    x + 2*y + 3*z\nsubject to:\n    x + y >= 2\n    y + z >= 3\n",
+        "maximize:\n    x + -y\nsubject to:\n    2*x + 3*y = 12\n",
+    ];
+
+    for source in instances.iter() {
+        let [ew, jr, reference] = objectives(&parse(source), 8);
+        assert_eq!(ew, reference, "ew on {}", source);
+        assert_eq!(jr, reference, "jr on {}", source);
+    }
+}
+
+#[test]
+fn binary_knapsack() {
+    let ilp = parse("maximize:\n    5*x + y\nsubject to:\n    2*x + y <= 4\nbinary x\n");
+
+    // x = 2 would be better without the binary declaration
+    assert_eq!(objectives(&ilp, 4), [7.0; 3]);
+}
+
+#[test]
+fn progress_events() {
+    let ilp = parse("maximize:\n    -x1 + 4*x2 + 3*x3 + -x4\nsubject to:\n    x1 + x3 + 2*x4 = 4\n    x1 + 2*x2 + x4 = 2\n");
+    let options = SolverOptions::default();
+
+    let mut depths = Vec::new();
+    let mut iterations = Vec::new();
+    steinitz::solve(&ilp, &options, &mut |event| match event {
+        ProgressEvent::GraphDepth { depth, .. } => {
+            assert!(iterations.is_empty(), "the graph is constructed before Bellman-Ford runs");
+            depths.push(depth);
+        },
+        ProgressEvent::BellmanFordIter { n } => iterations.push(n),
+        _ => {}
+    }).unwrap();
+    assert_eq!(depths, (1..=depths.len()).collect::<Vec<_>>());
+    assert_eq!(iterations, (1..=iterations.len()).collect::<Vec<_>>());
+    assert!(!depths.is_empty() && !iterations.is_empty());
+
+    let mut sizes = Vec::new();
+    discrepancy::solve(&ilp, &options, &mut |event| {
+        if let ProgressEvent::TableSize { size } = event {
+            sizes.push(size);
+        }
+    }).unwrap();
+    // the table only grows
+    assert!(!sizes.is_empty());
+    assert!(sizes.windows(2).all(|w| w[0] <= w[1]), "{:?}", sizes);
+}
+
+#[test]
+fn sparse_matrix() {
+    // every column has a single non-zero entry, ew adds sparse columns
+    let ilp = parse("maximize:\n    x1 + 2*x2 + x3 + 3*x4 + x5 + x6 + 2*x7 + x8 + x9 + x10\nsubject to:\n    x1 + x2 = 1\n    x3 + x4 = 1\n    x5 + x6 = 1\n    x7 + x8 = 1\n    x9 + x10 = 1\n");
+    assert!(ilp.A.density() < SPARSE_DENSITY);
+
+    let ew = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    let reference = reference::brute_force_solve(&ilp, 1).unwrap();
+    assert_eq!(ew, reference);
+    assert_eq!(ilp.objective_value(&ew), 9.0);
+}
+
+#[test]
+fn min_l1_tiebreak() {
+    // every solution has the same cost, 5+5+5 is the only one with 3 items
+    let ilp = parse("maximize:\n    2*x + 5*y + 3*z + 4*w\nsubject to:\n    2*x + 5*y + 3*z + 4*w = 15\n");
+    let min_l1 = SolverOptions { tiebreak: TieBreak::MinL1, ..SolverOptions::default() };
+
+    // brute force: the solution with the smallest 1-norm
+    let mut sparsest:Option<Vector> = None;
+    for x in 0..=7 {
+        for y in 0..=3 {
+            for z in 0..=5 {
+                for w in 0..=3 {
+                    let v = Vector::from_slice(&[x, y, z, w]);
+                    let better = match &sparsest {
+                        Some(s) => v.one_norm() < s.one_norm(),
+                        None => true
+                    };
+                    if ilp.A.row(0).dot(&v) == 15 && better {
+                        sparsest = Some(v);
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(sparsest, Some(Vector::from_slice(&[0, 3, 0, 0])));
+
+    let ew = steinitz::solve(&ilp, &min_l1, &mut |_| {}).unwrap();
+    let jr = discrepancy::solve(&ilp, &min_l1, &mut |_| {}).unwrap();
+    assert_eq!(ew, Vector::from_slice(&[0, 3, 0, 0]));
+    assert_eq!(jr, Vector::from_slice(&[0, 3, 0, 0]));
+}
+
+#[test]
+fn knapsack_dynamic_program() {
+    let ilp = parse("maximize:\n    7*a + 9*b + 4*c + 3*d\nsubject to:\n    5*a + 6*b + 3*c + 2*d <= 17\n");
+    let options = SolverOptions::default();
+    assert!(knapsack::applies(&ilp));
+
+    let dp = knapsack::solve(&ilp, &options).unwrap();
+    let ew = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+    assert_eq!(ilp.A.row(0).dot(&dp), 17);
+    assert_eq!(dp.dot(&ilp.c), ew.dot(&ilp.c));
+    assert_eq!(dp.dot(&ilp.c), 25);
+
+    let two_rows = parse("maximize:\n    x + y\nsubject to:\n    x + 2*y <= 4\n    x + -y <= 1\n");
+    assert!(!knapsack::applies(&two_rows));
+    let negative = parse("maximize:\n    x + y\nsubject to:\n    x + -y = 1\n");
+    assert!(!knapsack::applies(&negative));
+}
+
+#[test]
+fn secondary_objective_breaks_ties() {
+    // every x with x1 + x2 + x3 = 4 is optimal for the primary objective
+    let model = |secondary:&str| parse(&format!(
+        "maximize:\n    x1 + x2 + x3\nthen {}:\n    x1\nsubject to:\n    x1 + x2 + x3 = 4\n    x1 + 2*x2 <= 5\n", secondary
+    ));
+    let options = SolverOptions::default();
+
+    for &(secondary, x1) in &[("minimize", 0), ("maximize", 4)] {
+        let ilp = model(secondary);
+        let ew = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+        let jr = discrepancy::solve(&ilp, &options, &mut |_| {}).unwrap();
+
+        for x in &[ew, jr] {
+            assert_eq!(ilp.objective_value(x), 4.0);
+            assert_eq!(x.iter().next(), Some(&x1), "{} x1: {:?}", secondary, x);
+        }
+    }
+}
+
+#[test]
+fn min_support_tiebreak() {
+    // every solution has the same cost, only 11*a uses a single column
+    let ilp = parse("maximize:\n    a + 5*b + 6*c + 6*d\nsubject to:\n    a + 5*b + 6*c + 6*d = 11\n");
+    let solve = |tiebreak| discrepancy::solve(&ilp, &SolverOptions { tiebreak, ..SolverOptions::default() }, &mut |_| {}).unwrap();
+
+    assert_eq!(solve(TieBreak::First).nnz(), 2);
+    // b + c has the smaller 1-norm
+    assert_eq!(solve(TieBreak::MinL1).nnz(), 2);
+    assert_eq!(solve(TieBreak::MinSupport), Vector::from_slice(&[11, 0, 0, 0]));
+}
@@ -0,0 +1,427 @@
+use intopt::ilp::{parser, steinitz, SolverOptions, SolveResult, ProgressEvent, TieBreak};
+use intopt::{ILP, ILPError, Vector};
+use std::time::Duration;
+
+fn parse(source:&str) -> ILP {
+    parser::parse_str(source).unwrap()
+}
+
+/// x + y + s = 2: x then y and y then x reach the same vertex.
+fn overlapping() -> ILP {
+    parse("maximize:\n    x + 2*y\nsubject to:\n    x + y <= 2\n")
+}
+
+#[test]
+fn overlapping_paths_are_merged() {
+    let (result, stats) = steinitz::solve_with_stats(&overlapping(), &SolverOptions::default(), &mut |_| {});
+
+    assert_eq!(result.unwrap(), Vector::from_slice(&[0, 2, 0]));
+    assert!(stats.merged > 0);
+    assert!(stats.merged <= stats.accepted && stats.accepted <= stats.candidates);
+}
+
+#[test]
+fn multiple_targets_match_independent_solves() {
+    let options = SolverOptions::default();
+    let model = |rhs:(i32, i32)| parse(&format!(
+        "maximize:\n    3*x + 2*y + z\nsubject to:\n    x + y + -z = {}\n    x + -y + 2*z = {}\n", rhs.0, rhs.1
+    ));
+    let targets = [(2, 1), (3, 4)];
+
+    let ilp = model(targets[0]);
+    let b:Vec<Vector> = targets.iter().map(|&(b1, b2)| Vector::from_slice(&[b1, b2])).collect();
+    let results = steinitz::solve_multi(&ilp, &b, &options, &mut |_| {});
+
+    for (result, &rhs) in results.into_iter().zip(targets.iter()) {
+        let single = model(rhs);
+        let expected = steinitz::solve(&single, &options, &mut |_| {}).unwrap();
+        assert_eq!(result.unwrap().dot(&ilp.c), expected.dot(&single.c), "b = {:?}", rhs);
+    }
+}
+
+#[test]
+fn bound_factor_one_is_exact() {
+    let ilp = parse("maximize:\n    2*x + 3*y + z\nsubject to:\n    x + y + z = 4\n    x + 2*y <= 5\n");
+    let mut options = SolverOptions::default();
+    assert_eq!(options.bound_factor, 1.0);
+
+    match steinitz::solve2(&ilp, &options, &mut |_| {}) {
+        SolveResult::Optimal { objective, .. } => assert_eq!(objective, 9),
+        _ => panic!("factor 1.0 has to find the optimum")
+    }
+
+    // a smaller search region does not prove optimality
+    options.bound_factor = 0.5;
+    assert!(!matches!(steinitz::solve2(&ilp, &options, &mut |_| {}), SolveResult::Optimal { .. }));
+}
+
+#[test]
+fn zero_objective_skips_bellman_ford() {
+    let count = |objective:&str| {
+        let ilp = parse(&format!("maximize:\n    {}\nsubject to:\n    x + 2*y + -z = 1\n    x + -y + z = 2\n", objective));
+        let mut iterations = 0;
+
+        steinitz::solve(&ilp, &SolverOptions::default(), &mut |event| {
+            if let ProgressEvent::BellmanFordIter { .. } = event {
+                iterations += 1;
+            }
+        }).unwrap();
+        iterations
+    };
+
+    assert!(count("x + -y") > 0);
+    assert_eq!(count("0*x + 0*y + 0*z"), 0);
+}
+
+#[test]
+fn bellman_ford_iteration_cap() {
+    // the paths found by the construction are improved in several iterations
+    let ilp = parse("maximize:\n    -x1 + 4*x2 + 3*x3 + -x4\nsubject to:\n    x1 + x3 + 2*x4 = 4\n    x1 + 2*x2 + x4 = 2\n");
+    let options = SolverOptions { max_bf_iterations: Some(1), ..SolverOptions::default() };
+    assert!(matches!(
+        steinitz::solve2(&ilp, &options, &mut |_| {}),
+        SolveResult::Feasible { proven_optimal: false, .. }
+    ));
+
+    let options = SolverOptions { max_bf_iterations: Some(10_000), ..SolverOptions::default() };
+    assert!(matches!(steinitz::solve2(&ilp, &options, &mut |_| {}), SolveResult::Optimal { objective: 16, .. }));
+
+    // a cap of |V| - 1 iterations does not limit the algorithm, the positive cycle is still found
+    let unbounded = parse("maximize:\n    2*x1 + -x2 + y\nsubject to:\n    x1 + -x2 + y = 2\n");
+    let (_, stats) = steinitz::solve_with_stats(&unbounded, &SolverOptions::default(), &mut |_| {});
+    let options = SolverOptions { max_bf_iterations: Some(stats.vertices - 1), ..SolverOptions::default() };
+    assert!(matches!(steinitz::solve2(&unbounded, &options, &mut |_| {}), SolveResult::Unbounded { .. }));
+}
+
+#[test]
+fn solution_path_adds_up_to_the_objective() {
+    let ilp = parse("maximize:\n    -x1 + 4*x2 + 3*x3 + -x4\nsubject to:\n    x1 + x3 + 2*x4 = 4\n    x1 + 2*x2 + x4 = 2\n");
+    let (x, path) = steinitz::solve_with_path(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+
+    let mut cost = 0;
+    for &(j, cost_so_far) in path.iter() {
+        cost += ilp.c.iter().nth(j).unwrap();
+        assert_eq!(cost_so_far, cost);
+    }
+    assert_eq!(cost, x.dot(&ilp.c));
+    assert_eq!(path.len() as i32, x.one_norm());
+}
+
+#[test]
+fn node_capacity_does_not_change_the_result() {
+    let ilp = parse("maximize:\n    -x1 + 4*x2 + 3*x3 + -x4\nsubject to:\n    x1 + x3 + 2*x4 = 4\n    x1 + 2*x2 + x4 = 2\n");
+    let mut options = SolverOptions::default();
+
+    for &capacity in [None, Some(1), Some(16), Some(1 << 16)].iter() {
+        options.node_capacity = capacity;
+        let x = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+        assert_eq!(x.dot(&ilp.c), 16, "capacity {:?}", capacity);
+    }
+}
+
+#[test]
+fn time_limit_returns_the_known_solution() {
+    // b is the first column, the optimum x2 = 5 needs a path of length 5
+    let ilp = parse("maximize:\n    x1 + 10*x2\nsubject to:\n    5*x1 + x2 = 5\n");
+    let options = SolverOptions { timeout: Some(Duration::from_secs(0)), ..SolverOptions::default() };
+
+    match steinitz::solve2(&ilp, &options, &mut |_| {}) {
+        SolveResult::Feasible { x, objective, proven_optimal: false } => {
+            assert_eq!(ilp.A.row(0).dot(&x), 5);
+            assert!(objective <= 50);
+        },
+        _ => panic!("expected a feasible solution that is not proven optimal")
+    }
+
+    assert!(matches!(steinitz::solve2(&ilp, &SolverOptions::default(), &mut |_| {}), SolveResult::Optimal { objective: 50, .. }));
+}
+
+#[test]
+fn time_limit_falls_back_to_the_heuristic() {
+    // b is far away, the construction stops long before it reaches b
+    let ilp = parse("maximize:\n    x1 + x2 + 3*x3\nsubject to:\n    x1 + x3 = 4000\n    x2 + x3 = 3000\n");
+    let options = SolverOptions { timeout: Some(Duration::from_secs(0)), ..SolverOptions::default() };
+
+    match steinitz::solve2(&ilp, &options, &mut |_| {}) {
+        SolveResult::Feasible { x, objective, proven_optimal: false } => {
+            for (i, &b) in ilp.b.iter().enumerate() {
+                assert_eq!(ilp.A.row(i).dot(&x), b);
+            }
+            assert_eq!(objective, x.dot(&ilp.c));
+        },
+        _ => panic!("expected the heuristic solution")
+    }
+}
+
+#[test]
+fn cycles_and_unboundedness() {
+    let options = SolverOptions::default();
+    let solve = |objective:&str| {
+        let ilp = parse(&format!("maximize:\n    {}\nsubject to:\n    x1 + -x2 + y = 2\n", objective));
+        steinitz::solve(&ilp, &options, &mut |_| {}).map(|x| x.dot(&ilp.c))
+    };
+
+    // x1 + x2 and x2 + y are cycles (Ay = 0) with cost -1, the path to b does not use them
+    assert!(matches!(solve("x1 + -2*x2 + y"), Ok(2)));
+    // ... with cost 0 they do not change the optimum either
+    assert!(matches!(solve("x1 + -x2 + y"), Ok(2)));
+    // x1 + x2 has cost 1 and can be added to any solution
+    assert!(matches!(solve("2*x1 + -x2 + y"), Err(ILPError::Unbounded)));
+}
+
+#[test]
+fn boundary_vertices_are_counted() {
+    let ilp = parse("maximize:\n    5*x + y\nsubject to:\n    2*x + y <= 4\nbinary x\n");
+    let (_, stats) = steinitz::solve_with_stats(&ilp, &SolverOptions::default(), &mut |_| {});
+
+    assert!(stats.boundary_vertices > 0);
+    assert!(stats.boundary_vertices < stats.vertices);
+}
+
+#[test]
+fn surface_sizes_per_depth() {
+    let ilp = parse("maximize:\n    5*x + y\nsubject to:\n    2*x + y <= 4\nbinary x\n");
+    let (_, stats) = steinitz::solve_with_stats(&ilp, &SolverOptions::default(), &mut |_| {});
+
+    assert_eq!(stats.surface_sizes.len(), stats.depth);
+    assert_eq!(stats.surface_sizes.iter().max(), Some(&stats.max_surface_size));
+    // every vertex except the origin was new at some depth
+    assert_eq!(stats.surface_sizes.iter().sum::<usize>() + 1, stats.vertices);
+}
+
+#[test]
+fn radius_schedule() {
+    let ilp = parse("maximize:\n    x + y + z\nsubject to:\n    x + y + -z = 2\n    x + -y + 2*z = 7\n");
+    let (m, delta_a) = (2.0, ilp.delta_A() as f32);
+
+    // ‖b‖ exceeds Δ, so the schedule starts at its cap 2Δm ...
+    assert_eq!(steinitz::initial_radius(&ilp), 2.0 * delta_a * m);
+    // ... and decreases towards Δm
+    assert_eq!(steinitz::converged_radius(&ilp), delta_a * m);
+    let mut previous = steinitz::initial_radius(&ilp);
+    for &depth in &[2, 4, 16, 1024, 1 << 20] {
+        let r = steinitz::radius_at_depth(&ilp, depth);
+        assert!(r <= previous, "the radius grows at depth {}", depth);
+        assert!(r >= steinitz::converged_radius(&ilp));
+        previous = r;
+    }
+    assert!(previous - steinitz::converged_radius(&ilp) < 1e-3);
+}
+
+#[test]
+fn candidate_counts_are_consistent() {
+    let ilp = parse("maximize:\n    x + 2*y + 3*z\nsubject to:\n    x + y + -z = 1\n    x + -y + 2*z = 4\n");
+    let (result, stats) = steinitz::solve_with_stats(&ilp, &SolverOptions::default(), &mut |_| {});
+
+    assert!(result.is_ok());
+    assert!(stats.accepted > 0 && stats.accepted <= stats.candidates);
+    // every accepted candidate adds an edge and a new vertex unless it is merged
+    assert_eq!(stats.edges, stats.accepted);
+    assert_eq!(stats.vertices, 1 + stats.accepted - stats.merged);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn graphs_are_validated_in_debug_builds() {
+    // solve and solve_multi check every node and edge of the constructed
+    // graph in debug builds (and panic if one is inconsistent)
+    let options = SolverOptions::default();
+
+    // with merged vertices, i.e. nodes with several incoming edges
+    let (_, stats) = steinitz::solve_with_stats(&overlapping(), &options, &mut |_| {});
+    assert!(stats.merged > 0);
+
+    // with cycles
+    let cyclic = parse("maximize:\n    x1 + -2*x2 + y\nsubject to:\n    x1 + -x2 + y = 2\n");
+    assert!(steinitz::solve(&cyclic, &options, &mut |_| {}).is_ok());
+
+    // with several targets
+    let ilp = parse("maximize:\n    3*x + 2*y + z\nsubject to:\n    x + y + -z = 2\n    x + -y + 2*z = 1\n");
+    let b = [Vector::from_slice(&[2, 1]), Vector::from_slice(&[3, 4])];
+    assert!(steinitz::solve_multi(&ilp, &b, &options, &mut |_| {}).iter().all(Result::is_ok));
+}
+
+#[test]
+fn large_right_hand_side() {
+    // ‖b‖ > 2^24: s*b is not exact with f32 and ‖b‖² exceeds the i32 range
+    let ilp = parse("maximize:\n    x + y\nsubject to:\n    16777216*x + 16777217*y = 50331650\n");
+    let (result, stats) = steinitz::solve_with_stats(&ilp, &SolverOptions::default(), &mut |_| {});
+
+    assert_eq!(result.unwrap(), Vector::from_slice(&[1, 2]));
+    assert!(stats.vertices < 100);
+
+    // b = 2*16777216 + 16777217
+    let ilp = parse("maximize:\n    x + y\nsubject to:\n    16777216*x + 16777217*y = 50331649\n");
+    let x = steinitz::solve(&ilp, &SolverOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(x, Vector::from_slice(&[2, 1]));
+}
+
+#[test]
+fn construction_stops_at_the_upper_bound() {
+    // c = a_1, every solution meets the bound c*x <= b_1 = 6
+    let ilp = parse("maximize:\n    x + y + z\nsubject to:\n    x + y + z = 6\n    x + -y = 0\n");
+    assert_eq!(ilp.objective_upper_bound(), Some(6));
+
+    let (result, stats) = steinitz::solve_with_stats(&ilp, &SolverOptions::default(), &mut |_| {});
+    assert_eq!(result.unwrap().dot(&ilp.c), 6);
+
+    // ties are broken on the complete graph, there is no early stop
+    let options = SolverOptions { tiebreak: TieBreak::MinL1, ..SolverOptions::default() };
+    let (result, complete) = steinitz::solve_with_stats(&ilp, &options, &mut |_| {});
+    assert_eq!(result.unwrap().dot(&ilp.c), 6);
+    assert!(stats.depth < complete.depth && stats.vertices < complete.vertices);
+}
+
+#[test]
+fn solve_multi_runs_bellman_ford_once() {
+    let ilp = parse("maximize:\n    3*x + 2*y + z\nsubject to:\n    x + y + -z = 2\n    x + -y + 2*z = 1\n");
+    let b = [Vector::from_slice(&[2, 1]), Vector::from_slice(&[3, 4]), Vector::from_slice(&[1, 5])];
+    let options = SolverOptions::default();
+    // every run of the Bellman-Ford algorithm starts with iteration 1
+    let mut runs = 0;
+
+    let results = steinitz::solve_multi(&ilp, &b, &options, &mut |event| {
+        if let ProgressEvent::BellmanFordIter { n: 1 } = event {
+            runs += 1;
+        }
+    });
+    assert_eq!(runs, 1);
+    assert_eq!(results.len(), 3);
+
+    let mut single_runs = 0;
+    for (result, b) in results.into_iter().zip(b.iter()) {
+        let single = parse(&format!(
+            "maximize:\n    3*x + 2*y + z\nsubject to:\n    x + y + -z = {}\n    x + -y + 2*z = {}\n",
+            b.iter().next().unwrap(), b.iter().nth(1).unwrap()
+        ));
+        let expected = steinitz::solve(&single, &options, &mut |event| {
+            if let ProgressEvent::BellmanFordIter { n: 1 } = event {
+                single_runs += 1;
+            }
+        });
+        assert_eq!(result.unwrap().dot(&ilp.c), expected.unwrap().dot(&single.c), "b = {:?}", b);
+    }
+    assert_eq!(single_runs, 3);
+}
+
+#[test]
+fn memory_limit() {
+    // the search region is a tube of radius up to 2Δm = 18 around the segment from 0 to b
+    let ilp = parse(
+        "maximize:\n    x1 + 2*x2 + x3 + 3*x4 + x5 + x6\nsubject to:\n    \
+        3*x1 + -2*x2 + x3 + 2*x4 + -x5 + x6 = 400\n    \
+        x1 + 3*x2 + -2*x3 + x4 + 2*x5 + -x6 = 370\n    \
+        -x1 + x2 + 3*x3 + -2*x4 + x5 + 2*x6 = 350\n"
+    );
+    let options = SolverOptions { max_memory: Some(1 << 20), ..SolverOptions::default() };
+    let (result, stats) = steinitz::solve_with_stats(&ilp, &options, &mut |_| {});
+
+    assert!(matches!(result, Err(ILPError::ResourceExhausted)));
+    assert!(stats.vertices > 0);
+
+    // a limit that is not reached does not change the result
+    let (result, _) = steinitz::solve_with_stats(&overlapping(), &options, &mut |_| {});
+    assert_eq!(result.unwrap(), Vector::from_slice(&[0, 2, 0]));
+}
+
+#[test]
+fn solve_result_variants() {
+    let options = SolverOptions::default();
+
+    let ilp = parse("maximize:\n    x + 2*y\nsubject to:\n    x + y = 3\n");
+    match steinitz::solve2(&ilp, &options, &mut |_| {}) {
+        SolveResult::Optimal { x, objective } => {
+            assert_eq!(x, Vector::from_slice(&[0, 3]));
+            assert_eq!(objective, 6);
+        },
+        _ => panic!("the ILP has an optimal solution")
+    }
+
+    let ilp = parse("maximize:\n    x + y\nsubject to:\n    2*x + 2*y = 3\n");
+    assert!(matches!(steinitz::solve2(&ilp, &options, &mut |_| {}), SolveResult::Infeasible));
+
+    let ilp = parse("maximize:\n    x + y\nsubject to:\n    x + -y = 1\n");
+    assert!(matches!(steinitz::solve2(&ilp, &options, &mut |_| {}), SolveResult::Unbounded { .. }));
+
+    // conversions from and to the plain result of the solvers
+    let x = Vector::from_slice(&[1, 2]);
+    let ilp = parse("maximize:\n    x + 2*y\nsubject to:\n    x + y = 3\n");
+    match SolveResult::new(&ilp, Ok((x.clone(), false))) {
+        SolveResult::Feasible { x: y, objective: 5, proven_optimal: false } => assert_eq!(y, x),
+        _ => panic!("x is not proven to be optimal")
+    }
+    assert!(matches!(SolveResult::new(&ilp, Err(ILPError::Timeout)), SolveResult::Stopped(ILPError::Timeout)));
+    assert!(matches!(SolveResult::new(&ilp, Err(ILPError::Unbounded)), SolveResult::Unbounded { ray: None }));
+
+    let result:Result<Vector, ILPError> = SolveResult::Feasible { x: x.clone(), objective: 5, proven_optimal: false }.into();
+    assert_eq!(result.unwrap(), x);
+    let result:Result<Vector, ILPError> = SolveResult::Infeasible.into();
+    assert!(matches!(result, Err(ILPError::NoSolution)));
+    let result:Result<Vector, ILPError> = SolveResult::Unbounded { ray: None }.into();
+    assert!(matches!(result, Err(ILPError::Unbounded)));
+    let result:Result<Vector, ILPError> = SolveResult::Stopped(ILPError::ResourceExhausted).into();
+    assert!(matches!(result, Err(ILPError::ResourceExhausted)));
+}
+
+#[test]
+fn region_points_bound_the_graph_size() {
+    let ilp = parse("maximize:\n    x + -2*y + 3*z\nsubject to:\n    x + -y + 2*z = 10\n");
+
+    // m = 1: the integers in [-radius, b + radius]
+    assert_eq!(steinitz::radius_at_depth(&ilp, 1), 4.0);
+    assert_eq!(steinitz::count_region_points(&ilp, 1), 19.0);
+
+    // the region shrinks with the radius, the vertices of every depth lie within it
+    let mut nodes = 1;
+    steinitz::solve(&ilp, &SolverOptions::default(), &mut |event| {
+        if let ProgressEvent::GraphDepth { depth, surface } = event {
+            assert!(surface as f64 <= steinitz::count_region_points(&ilp, depth as i32));
+            nodes += surface;
+        }
+    }).unwrap();
+    assert_eq!(nodes, 18);
+    assert!(nodes as f64 <= steinitz::count_region_points(&ilp, 1));
+}
+
+#[test]
+fn repeated_solves_are_identical() {
+    // many optimal solutions: which one is returned must not depend on the hash map state
+    let ilp = parse("maximize:\n    x1 + x2 + x3 + x4 + 2*x5\nsubject to:\n    x1 + x2 + x3 + x4 + 2*x5 <= 7\n    x1 + -x2 + x3 + -x4 = 1\n");
+    let options = SolverOptions::default();
+
+    let first = steinitz::solve(&ilp, &options, &mut |_| {}).unwrap();
+    assert_eq!(ilp.objective_value(&first), 7.0);
+    for _ in 0..20 {
+        assert_eq!(steinitz::solve(&ilp, &options, &mut |_| {}).unwrap(), first);
+    }
+}
+
+#[test]
+fn reset_graph_matches_independent_solves() {
+    let options = SolverOptions::default();
+    let model = |rhs:(i32, i32)| parse(&format!(
+        "maximize:\n    3*x + 2*y + z\nsubject to:\n    x + y + -z = {}\n    x + -y + 2*z = {}\n", rhs.0, rhs.1
+    ));
+    let targets = [(2, 1), (3, 4)];
+
+    let ilp = model(targets[0]);
+    let b:Vec<Vector> = targets.iter().map(|&(b1, b2)| Vector::from_slice(&[b1, b2])).collect();
+    let mut graph = steinitz::build_graph(&ilp, &b, &options, &mut |_| {}).unwrap();
+
+    for (b, &rhs) in b.iter().zip(targets.iter()) {
+        graph.reset_path_state();
+        let x = steinitz::solve_on_graph(&ilp, &mut graph, b, &options, &mut |_| {}).unwrap();
+
+        let single = model(rhs);
+        let expected = steinitz::solve(&single, &options, &mut |_| {}).unwrap();
+        assert_eq!(x, expected, "b = {:?}", rhs);
+    }
+
+    // after a reset the graph can be reused for another objective (without
+    // the reset the paths of the old objective would be returned)
+    let c = Vector::from_slice(&[5, -1, -2]);
+    graph.reset_path_state();
+    let x = steinitz::solve_on_graph(&ilp.clone().with_cost(c.clone()), &mut graph, &b[1], &options, &mut |_| {}).unwrap();
+    assert_eq!(x, Vector::from_slice(&[3, 1, 1]));
+    assert_eq!(x, steinitz::solve(&model(targets[1]).with_cost(c), &options, &mut |_| {}).unwrap());
+}
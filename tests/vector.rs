@@ -0,0 +1,90 @@
+use intopt::Vector;
+use intopt::ilp::SparseVector;
+
+#[test]
+fn linf_distance() {
+    let a = Vector::from_slice(&[-3, 4, 0]);
+    let b = Vector::from_slice(&[2, -1, -7]);
+
+    assert_eq!(a.linf_distance(&b), 7);
+    assert_eq!(b.linf_distance(&a), 7);
+    assert_eq!(a.linf_distance(&a), 0);
+    assert!(a.max_distance(&b, 7));
+    assert!(!a.max_distance(&b, 6));
+}
+
+#[test]
+fn sparse_vector_matches_dense_arithmetic() {
+    let column = Vector::from_slice(&[0, 3, 0, 0, -2, 0]);
+    let sparse = SparseVector::from_dense(&column);
+    let mut x = Vector::from_slice(&[1, 1, 1, 1, 1, 1]);
+    let original = x.clone();
+
+    sparse.add_to(&mut x);
+    assert_eq!(x, original.add(&column));
+    sparse.subtract_from(&mut x);
+    assert_eq!(x, original);
+}
+
+#[test]
+fn pad_and_truncate() {
+    let v = Vector::from_slice(&[5, -2]);
+    let padded = v.pad(4, 0);
+
+    assert_eq!(padded, Vector::from_slice(&[5, -2, 0, 0]));
+    assert_eq!(padded.truncate(2), v);
+    assert_eq!(v.pad(3, 7), Vector::from_slice(&[5, -2, 7]));
+    assert_eq!(v.pad(2, 7), v);
+}
+
+#[test]
+#[should_panic(expected = "padding can not shrink a vector")]
+fn pad_can_not_shrink() {
+    Vector::from_slice(&[1, 2, 3]).pad(2, 0);
+}
+
+#[test]
+#[should_panic(expected = "truncating can not grow a vector")]
+fn truncate_can_not_grow() {
+    Vector::from_slice(&[1, 2]).truncate(3);
+}
+
+#[test]
+fn nnz() {
+    assert_eq!(Vector::zero(4).nnz(), 0);
+    assert_eq!(Vector::from_slice(&[0, -3, 0, 0]).nnz(), 1);
+    assert_eq!(Vector::from_slice(&[2, 0, -1, 5]).nnz(), 3);
+    assert_eq!(Vector::from_slice(&[1, 1, 1]).nnz(), 3);
+    assert_eq!(Vector::from_slice(&[]).nnz(), 0);
+}
+
+#[test]
+fn is_zero() {
+    assert!(Vector::zero(5).is_zero());
+    assert!(Vector::from_slice(&[]).is_zero());
+    assert!(!Vector::from_slice(&[0, 0, 0, -1, 0]).is_zero());
+    assert!(!Vector::from_slice(&[2, 0, 0, 0, 0]).is_zero());
+}
+
+#[test]
+fn inf_norm_takes_absolute_values() {
+    assert_eq!(Vector::from_slice(&[-7, 3, -2]).inf_norm(), 7);
+    assert_eq!(Vector::from_slice(&[-1, 4, -2]).inf_norm(), 4);
+    assert_eq!(Vector::from_slice(&[-3, -5]).inf_norm(), 5);
+    assert_eq!(Vector::zero(3).inf_norm(), 0);
+}
+
+#[test]
+fn add_sub_and_scale() {
+    let a = Vector::from_slice(&[4, -1, 0, 7]);
+    let b = Vector::from_slice(&[-2, 5, 3, 1]);
+
+    assert_eq!(a.add(&b), Vector::from_slice(&[2, 4, 3, 8]));
+    assert_eq!(a.sub(&b), Vector::from_slice(&[6, -6, -3, 6]));
+    assert_eq!(a.add(&b).sub(&b), a);
+    assert!(a.sub(&a).is_zero());
+
+    assert_eq!(a.scale(-3), Vector::from_slice(&[-12, 3, 0, -21]));
+    assert_eq!(a.scale(1), a);
+    assert!(a.scale(0).is_zero());
+}